@@ -0,0 +1,88 @@
+//compare the speed of every evaluation strategy, run with cargo bench
+
+use easy_lambda_calculus::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+//every strategy being compared, with its name
+const STRATEGIES: [(&str, Strategy); 7] = [
+    ("substitution", Strategy::Substitution),
+    ("krivine", Strategy::Krivine),
+    ("call by need", Strategy::CallByNeed),
+    ("call by value", Strategy::CallByValue),
+    ("optimal", Strategy::Optimal),
+    ("nbe", Strategy::Nbe),
+    ("bytecode", Strategy::Bytecode),
+];
+
+//church numeral n, %f|x.(f (f ... x))
+fn church(n: usize) -> Lambda {
+    let mut l = Lambda::Variable(Symbol::new("x"));
+    for _ in 0..n {
+        l = Lambda::Reducible((Box::new(Lambda::Variable(Symbol::new("f"))), Box::new(l)));
+    }
+    lambda!("%f|x.{}", l)
+}
+
+//lambdas to evaluate, with their names
+fn workloads() -> Vec<(String, Lambda)> {
+    let t = lambda!("%x|y.x");
+    let f = lambda!("%x|y.y");
+    let not = lambda!("%b.((b &{}) &{})", f.clone(), t.clone());
+    let mut out = Vec::new();
+    for n in [100, 500] {
+        //parity of a church numeral, by applying not n times
+        out.push((
+            format!("parity {}", n),
+            lambda!("({} &{}) &{}", church(n), not.clone(), t.clone()),
+        ));
+    }
+    //applying the church numeral 3 to itself, then to the identity twice
+    out.push((
+        "3 3 id id".to_string(),
+        lambda!("(({} &{}) (%x.x)) (%y.y)", church(3), church(3)),
+    ));
+    out
+}
+
+//time evaluating a lambda, repeating it until enough time has passed
+fn time(l: &Lambda, strategy: Strategy) -> Duration {
+    let mut runs = 0;
+    let start = Instant::now();
+    while runs < 10 || start.elapsed() < Duration::from_millis(500) {
+        black_box(l.clone().evaluate_with(strategy));
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+//count the beta reduction steps Lambda.evaluate() does
+fn substitution_steps(l: &Lambda) -> usize {
+    let mut l = l.clone().alpha_reduce();
+    let mut steps = 0;
+    while let Lambda::Reducible(_) = l {
+        l = l.reduce();
+        steps += 1;
+    }
+    steps
+}
+
+fn main() {
+    for (name, l) in workloads() {
+        let expected = DeBruijn::from(&l.clone().evaluate());
+        for (s, strategy) in STRATEGIES {
+            let out = DeBruijn::from(&l.clone().evaluate_with(strategy));
+            assert_eq!(out, expected, "{} on {}", s, name);
+            println!("{:<16} {:<16} {:?}", name, s, time(&l, strategy));
+        }
+        let mut net = InteractionNet::new(&DeBruijn::from(&l));
+        net.reduce();
+        println!(
+            "{:<16} {} interactions, {} beta steps shared, {} beta steps substituting",
+            name,
+            net.interactions(),
+            net.steps(),
+            substitution_steps(&l)
+        );
+    }
+}
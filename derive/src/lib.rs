@@ -0,0 +1,239 @@
+//! Derive macros for the IntoLambda and FromLambda traits of easy_lambda_calculus
+//!
+//! Use them through the `derive` feature of easy_lambda_calculus, which re-exports them next to the traits.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Data, DeriveInput, Error, Fields, GenericArgument, Ident, PathArguments, Type,
+    parse_macro_input, parse_quote,
+};
+
+///Derive IntoLambda, encoding every constructor with Lambda::constructor()
+///
+///Structs have a single constructor and enums one for every variant, in the order they are written, with the fields in the order they are written.
+///The data type is Scott encoded, or Church encoded with `#[lambda(church)]`, folding every field of type Self or Box<Self>.
+///
+///The type also gets lambda_constructors(), the lambdas building every constructor from its fields, and lambda_destructor(), the lambda matching on it.
+#[proc_macro_derive(IntoLambda, attributes(lambda))]
+pub fn derive_into_lambda(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match into_lambda(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+///Derive FromLambda, decoding the encoding IntoLambda gives the data type with Lambda.constructor_fields()
+///
+///Scott and Church encodings are both decoded, so `#[lambda(church)]` only changes IntoLambda.
+#[proc_macro_derive(FromLambda, attributes(lambda))]
+pub fn derive_from_lambda(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match from_lambda(input) {
+        Ok(t) => t.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+//a constructor of the data type, with the path to it and its fields
+struct Constructor {
+    path: TokenStream2,
+    fields: Fields,
+}
+
+//find the constructors of a struct or enum
+fn constructors(input: &DeriveInput) -> Result<Vec<Constructor>, Error> {
+    let name = &input.ident;
+    match &input.data {
+        Data::Struct(s) => Ok(vec![Constructor {
+            path: quote!(#name),
+            fields: s.fields.clone(),
+        }]),
+        Data::Enum(e) if e.variants.is_empty() => Err(Error::new(
+            Span::call_site(),
+            "Cannot encode an enum without variants as a lambda",
+        )),
+        Data::Enum(e) => Ok(e
+            .variants
+            .iter()
+            .map(|v| {
+                let v_name = &v.ident;
+                Constructor {
+                    path: quote!(#name::#v_name),
+                    fields: v.fields.clone(),
+                }
+            })
+            .collect()),
+        Data::Union(_) => Err(Error::new(
+            Span::call_site(),
+            "Cannot encode a union as a lambda",
+        )),
+    }
+}
+
+//check the lambda attributes of the data type, returning true if it is Church encoded
+fn church(input: &DeriveInput) -> Result<bool, Error> {
+    let mut church = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("lambda")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("church") {
+                church = true;
+                Ok(())
+            } else if meta.path.is_ident("scott") {
+                church = false;
+                Ok(())
+            } else {
+                Err(meta.error("expected church or scott"))
+            }
+        })?;
+    }
+    Ok(church)
+}
+
+//check if a field holds the data type itself, directly or in a box
+fn recursive(ty: &Type, name: &Ident) -> bool {
+    if let Type::Path(p) = ty
+        && p.qself.is_none()
+        && let Some(last) = p.path.segments.last()
+    {
+        if (last.ident == *name || last.ident == "Self") && p.path.segments.len() == 1 {
+            return true;
+        }
+        if last.ident == "Box"
+            && let PathArguments::AngleBracketed(args) = &last.arguments
+            && let Some(GenericArgument::Type(inner)) = args.args.first()
+        {
+            return recursive(inner, name);
+        }
+    }
+    false
+}
+
+//the pattern binding every field of a constructor, and the names it binds them to
+fn pattern(c: &Constructor) -> (TokenStream2, Vec<Ident>) {
+    let path = &c.path;
+    let names: Vec<Ident> = (0..c.fields.len())
+        .map(|i| format_ident!("field{}", i))
+        .collect();
+    let pattern = match &c.fields {
+        Fields::Named(f) => {
+            let idents = f.named.iter().map(|f| &f.ident);
+            quote!(#path { #(#idents: #names),* })
+        }
+        Fields::Unnamed(_) => quote!(#path(#(#names),*)),
+        Fields::Unit => quote!(#path),
+    };
+    (pattern, names)
+}
+
+fn into_lambda(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let cs = constructors(&input)?;
+    let church = church(&input)?;
+    let name = input.ident.clone();
+    let count = cs.len();
+    let mut arms = Vec::new();
+    let mut functions = Vec::new();
+    for (i, c) in cs.iter().enumerate() {
+        let (pattern, names) = pattern(c);
+        let rec: Vec<bool> = c
+            .fields
+            .iter()
+            .map(|f| church && recursive(&f.ty, &name))
+            .collect();
+        arms.push(quote! {
+            #pattern => ::easy_lambda_calculus::Lambda::constructor(
+                #count,
+                #i,
+                vec![#(::easy_lambda_calculus::IntoLambda::into_lambda(#names)),*],
+                &[#(#rec),*],
+            ),
+        });
+        functions.push(quote! {
+            ::easy_lambda_calculus::Lambda::constructor_function(#count, #i, &[#(#rec),*])
+        });
+    }
+    let params: Vec<Ident> = input
+        .generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for p in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#p: ::easy_lambda_calculus::IntoLambda));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::easy_lambda_calculus::IntoLambda for #name #ty_generics #where_clause {
+            fn into_lambda(self) -> ::easy_lambda_calculus::Lambda {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            ///Lambdas building every constructor from its fields, in the order they are written
+            pub fn lambda_constructors() -> ::std::vec::Vec<::easy_lambda_calculus::Lambda> {
+                vec![#(#functions),*]
+            }
+            ///Lambda matching on the data, applying it to one function for every constructor
+            pub fn lambda_destructor() -> ::easy_lambda_calculus::Lambda {
+                ::easy_lambda_calculus::Lambda::destructor(#count)
+            }
+        }
+    })
+}
+
+fn from_lambda(mut input: DeriveInput) -> Result<TokenStream2, Error> {
+    let cs = constructors(&input)?;
+    church(&input)?;
+    let name = input.ident.clone();
+    let count = cs.len();
+    let expected = format!("an encoded {}", name);
+    let mut arms = Vec::new();
+    for (i, c) in cs.iter().enumerate() {
+        let path = &c.path;
+        let len = c.fields.len();
+        let decoded =
+            (0..len).map(|n| quote!(::easy_lambda_calculus::FromLambda::from_lambda(&f[#n])?));
+        let value = match &c.fields {
+            Fields::Named(f) => {
+                let idents = f.named.iter().map(|f| &f.ident);
+                quote!(#path { #(#idents: #decoded),* })
+            }
+            Fields::Unnamed(_) => quote!(#path(#(#decoded),*)),
+            Fields::Unit => quote!(#path),
+        };
+        arms.push(quote! {
+            ::std::option::Option::Some((#i, f)) if f.len() == #len => ::std::result::Result::Ok(#value),
+        });
+    }
+    let params: Vec<Ident> = input
+        .generics
+        .type_params()
+        .map(|p| p.ident.clone())
+        .collect();
+    let where_clause = input.generics.make_where_clause();
+    for p in params {
+        where_clause
+            .predicates
+            .push(parse_quote!(#p: ::easy_lambda_calculus::FromLambda));
+    }
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::easy_lambda_calculus::FromLambda for #name #ty_generics #where_clause {
+            fn from_lambda(
+                l: &::easy_lambda_calculus::Lambda,
+            ) -> ::std::result::Result<Self, ::easy_lambda_calculus::FromLambdaError> {
+                match l.constructor_fields(#count) {
+                    #(#arms)*
+                    _ => ::std::result::Result::Err(::easy_lambda_calculus::FromLambdaError::new(#expected, l)),
+                }
+            }
+        }
+    })
+}
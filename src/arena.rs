@@ -0,0 +1,251 @@
+//! Hash-consed store of lambdas, where identical lambdas are only stored once
+//!
+//! ```rust
+//! use easy_lambda_calculus::*;
+//! use easy_lambda_calculus::arena::{Node, TermArena};
+//!
+//! let mut arena = TermArena::new();
+//! let t = arena.from_lambda(&lambda!("(%x.x) (%x.x)"));
+//! if let Node::Reducible((a, b)) = arena.get(t) {
+//!     assert_eq!(a, b);
+//! }
+//! println!("{}", arena.len());
+//! //outputs 3
+//! let r = arena.reduce(t);
+//! println!("{}", arena.to_lambda(r));
+//! //outputs (%x.x)
+//! ```
+//!
+//! A Term is an id into a TermArena, so it is Copy, and two terms made in the same arena are equal exactly when they are the same lambda with the same variable names.
+
+use crate::{Lambda, Symbol};
+use std::collections::HashMap;
+
+///Id of a lambda stored in a TermArena
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Term(usize);
+
+///A lambda stored in a TermArena, with the lambdas inside it given as terms
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Node {
+    ///Variable
+    Variable(Symbol),
+    ///Function, with its variable name and output
+    Func((Symbol, Term)),
+    ///Marks a lambda being applied into a function
+    Reducible((Term, Term)),
+    ///Marks a section for alpha reduction
+    AlphaMark(Term),
+}
+
+///Store of lambdas where every distinct lambda is only stored once
+///
+///Adding a node that is already in the arena gives back the term it already has, so shared sub lambdas are never copied.
+///Terms are never removed, the whole arena is freed at once when it is dropped.
+#[derive(Debug, Default)]
+pub struct TermArena {
+    nodes: Vec<Node>,
+    terms: HashMap<Node, Term>,
+}
+
+impl TermArena {
+    ///Make a new empty arena
+    pub fn new() -> TermArena {
+        TermArena::default()
+    }
+    ///Add a node to the arena, or find the term it already has
+    pub fn add(&mut self, node: Node) -> Term {
+        if let Some(t) = self.terms.get(&node) {
+            return *t;
+        }
+        let t = Term(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.terms.insert(node, t);
+        t
+    }
+    ///Get the node of a term
+    pub fn get(&self, t: Term) -> &Node {
+        &self.nodes[t.0]
+    }
+    ///Number of distinct lambdas in the arena
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+    ///Check if the arena is empty
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+    ///Add a lambda to the arena, sharing every sub lambda that is already in it
+    ///
+    ///The arena only holds pure lambdas, adding a lambda with constants panics.
+    pub fn from_lambda(&mut self, l: &Lambda) -> Term {
+        //steps left to do, visiting a lambda or building a node from the finished terms
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Func(Symbol),
+            Reducible,
+            AlphaMark,
+        }
+        let mut stack = vec![Step::Visit(l)];
+        let mut out: Vec<Term> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Lambda::Variable(a)) => out.push(self.add(Node::Variable(*a))),
+                Step::Visit(Lambda::Func((a, b))) => match &**a {
+                    Lambda::Variable(c) => {
+                        stack.push(Step::Func(*c));
+                        stack.push(Step::Visit(b));
+                    }
+                    _ => panic!("Cannot add {:?}", a),
+                },
+                Step::Visit(Lambda::Reducible((a, b))) => {
+                    stack.push(Step::Reducible);
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::AlphaMark(a)) => {
+                    stack.push(Step::AlphaMark);
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::Constant(c)) => {
+                    panic!("Cannot add constant {}, arenas hold pure lambdas", c)
+                }
+                Step::Visit(l) => panic!("Cannot add {:?}", l),
+                Step::Func(a) => {
+                    let b = out.pop().unwrap();
+                    out.push(self.add(Node::Func((a, b))));
+                }
+                Step::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(self.add(Node::Reducible((a, b))));
+                }
+                Step::AlphaMark => {
+                    let a = out.pop().unwrap();
+                    out.push(self.add(Node::AlphaMark(a)));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+    ///Turn a term back into a lambda, copying every shared sub lambda
+    pub fn to_lambda(&self, t: Term) -> Lambda {
+        //steps left to do, visiting a term or building from the finished lambdas
+        enum Step {
+            Visit(Term),
+            Func(Symbol),
+            Reducible,
+            AlphaMark,
+        }
+        let mut stack = vec![Step::Visit(t)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(t) => match self.get(t) {
+                    Node::Variable(a) => out.push(Lambda::Variable(*a)),
+                    Node::Func((a, b)) => {
+                        stack.push(Step::Func(*a));
+                        stack.push(Step::Visit(*b));
+                    }
+                    Node::Reducible((a, b)) => {
+                        stack.push(Step::Reducible);
+                        stack.push(Step::Visit(*b));
+                        stack.push(Step::Visit(*a));
+                    }
+                    Node::AlphaMark(a) => {
+                        stack.push(Step::AlphaMark);
+                        stack.push(Step::Visit(*a));
+                    }
+                },
+                Step::Func(a) => {
+                    let b = out.pop().unwrap();
+                    out.push(Lambda::func(a, b));
+                }
+                Step::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(a.attach(b));
+                }
+                Step::AlphaMark => {
+                    let a = out.pop().unwrap();
+                    out.push(Lambda::AlphaMark(Box::new(a)));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+    ///Beta reduce a term once, in the same order as Lambda.reduce()
+    ///
+    ///The input is substituted once into every shared sub lambda, however many times it is used.
+    ///Parts of the term that do not change are shared with the new term.
+    pub fn reduce(&mut self, t: Term) -> Term {
+        //find the reducibles on the left side, down to the reducible with a function
+        let mut spine = vec![t];
+        loop {
+            match self.get(*spine.last().unwrap()) {
+                Node::Reducible((a, _)) => match self.get(*a) {
+                    Node::Func(_) => break,
+                    Node::Reducible(_) => spine.push(*a),
+                    _ => panic!("Cannot reduce"),
+                },
+                _ => panic!("Cannot reduce"),
+            }
+        }
+        let mut out = match self.get(spine.pop().unwrap()).clone() {
+            Node::Reducible((f, b)) => match self.get(f).clone() {
+                Node::Func((a, d)) => self.substitute(d, a, b),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        //rebuild the reducibles around the reduced one
+        while let Some(s) = spine.pop() {
+            if let Node::Reducible((_, b)) = *self.get(s) {
+                out = self.add(Node::Reducible((out, b)));
+            }
+        }
+        out
+    }
+    //substitute every instance of the variable like Lambda.reduce(), remembering the result for every term already substituted into
+    fn substitute(&mut self, t: Term, a: Symbol, sub: Term) -> Term {
+        let mut done: HashMap<Term, Term> = HashMap::new();
+        //terms left to visit, and whether their inside has already been substituted
+        let mut stack = vec![(t, false)];
+        while let Some((t, built)) = stack.pop() {
+            if done.contains_key(&t) {
+                continue;
+            }
+            let out = match self.get(t).clone() {
+                Node::Variable(b) => {
+                    if b == a {
+                        sub
+                    } else {
+                        t
+                    }
+                }
+                Node::AlphaMark(_) => t,
+                //a function shadowing the variable keeps its own
+                Node::Func((b, _)) if b == a => t,
+                Node::Func((b, c)) => {
+                    if !built {
+                        stack.push((t, true));
+                        stack.push((c, false));
+                        continue;
+                    }
+                    self.add(Node::Func((b, done[&c])))
+                }
+                Node::Reducible((b, c)) => {
+                    if !built {
+                        stack.push((t, true));
+                        stack.push((c, false));
+                        stack.push((b, false));
+                        continue;
+                    }
+                    self.add(Node::Reducible((done[&b], done[&c])))
+                }
+            };
+            done.insert(t, out);
+        }
+        done[&t]
+    }
+}
@@ -0,0 +1,474 @@
+//! Compiling lambdas to bytecode, run on a small virtual machine
+//!
+//! ```rust
+//! use easy_lambda_calculus::*;
+//! use easy_lambda_calculus::bytecode::{Program, Vm};
+//!
+//! let p = Program::compile(&lambda!("(%x.x) ((%y.y) (%z.z))"));
+//! print!("{}", p);
+//! //outputs closure 2
+//! //then var 0
+//! //then return
+//! //...
+//! //then apply
+//! //then return
+//! let bytes = p.to_bytes();
+//! let loaded = Program::from_bytes(&bytes).unwrap();
+//! let mut vm = Vm::new(&loaded);
+//! vm.run();
+//! println!("{} in {} steps", vm.read_back(), vm.steps());
+//! //outputs (%x.x) in 2 steps
+//! ```
+//!
+//! A Program only holds the instructions, so it can be compiled once, saved with Program.save() and loaded again with Program.load() for every run.
+
+use crate::{DeBruijn, Lambda};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::rc::Rc;
+
+//bytes at the start of every saved program, followed by the format version
+const MAGIC: &[u8] = b"LCBC";
+const VERSION: u8 = 1;
+
+///A single bytecode instruction
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Instr {
+    ///Push the value of a variable, numbered like DeBruijn::Variable
+    Var(usize),
+    ///Push a closure of the function whose body is the next instructions, with the number of instructions in the body including its return
+    Closure(usize),
+    ///Pop an input and a function, and call the function with the input
+    Apply,
+    ///Return from the current function, or stop the program
+    Return,
+}
+
+///Error found while decoding a saved program
+#[derive(Debug, PartialEq, Clone)]
+pub enum DecodeError {
+    ///The bytes do not start with the bytes every program starts with
+    BadMagic,
+    ///The program was saved with a different version of the format
+    Version(u8),
+    ///An instruction has an unknown opcode
+    BadOpcode(u8),
+    ///The bytes end in the middle of the program
+    UnexpectedEnd,
+    ///The instructions do not make a closed lambda
+    Malformed,
+}
+
+///Compiled lambda, as a list of instructions for a Vm
+///
+///Every function is compiled to a closure instruction followed by its body and a return, every reducible to the function, the input and an apply instruction.
+///The program ends with a return, stopping the Vm.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program {
+    code: Vec<Instr>,
+}
+
+impl Program {
+    ///Compile a closed lambda, panics if it has free variables or constants
+    pub fn compile(l: &Lambda) -> Program {
+        l.check_closed();
+        Program::from(&DeBruijn::from(l))
+    }
+    ///Get the instructions of the program
+    pub fn code(&self) -> &[Instr] {
+        &self.code
+    }
+    ///Encode the program as bytes, with every number written in as few bytes as it needs
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        write_number(&mut out, self.code.len());
+        for i in &self.code {
+            match i {
+                Instr::Var(n) => {
+                    out.push(0);
+                    write_number(&mut out, *n);
+                }
+                Instr::Closure(n) => {
+                    out.push(1);
+                    write_number(&mut out, *n);
+                }
+                Instr::Apply => out.push(2),
+                Instr::Return => out.push(3),
+            }
+        }
+        out
+    }
+    ///Decode a program encoded with Program.to_bytes()
+    pub fn from_bytes(bytes: &[u8]) -> Result<Program, DecodeError> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(DecodeError::BadMagic);
+        }
+        let mut pos = MAGIC.len();
+        match bytes.get(pos) {
+            Some(&VERSION) => pos += 1,
+            Some(v) => return Err(DecodeError::Version(*v)),
+            None => return Err(DecodeError::UnexpectedEnd),
+        }
+        let len = read_number(bytes, &mut pos)?;
+        let mut code = Vec::new();
+        for _ in 0..len {
+            let op = *bytes.get(pos).ok_or(DecodeError::UnexpectedEnd)?;
+            pos += 1;
+            code.push(match op {
+                0 => Instr::Var(read_number(bytes, &mut pos)?),
+                1 => Instr::Closure(read_number(bytes, &mut pos)?),
+                2 => Instr::Apply,
+                3 => Instr::Return,
+                _ => return Err(DecodeError::BadOpcode(op)),
+            });
+        }
+        let p = Program { code };
+        if pos != bytes.len() || !p.is_valid() {
+            return Err(DecodeError::Malformed);
+        }
+        Ok(p)
+    }
+    ///Save the program to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+    ///Load a program saved with Program.save()
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Program> {
+        let bytes = fs::read(path)?;
+        Program::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+    //check the instructions are a compiled closed lambda, so running them can never fail
+    fn is_valid(&self) -> bool {
+        //number of values every function body has pushed, and the instruction its return must be at
+        let mut bodies: Vec<(usize, usize)> = vec![(0, self.code.len().wrapping_sub(1))];
+        for (pc, i) in self.code.iter().enumerate() {
+            let (values, end) = bodies.last_mut().unwrap();
+            match i {
+                Instr::Var(n) => {
+                    if *n + 1 >= bodies.len() {
+                        return false;
+                    }
+                    bodies.last_mut().unwrap().0 += 1;
+                }
+                Instr::Closure(n) => {
+                    if *n == 0 || pc + *n >= *end {
+                        return false;
+                    }
+                    bodies.push((0, pc + *n));
+                }
+                Instr::Apply => {
+                    if *values < 2 {
+                        return false;
+                    }
+                    *values -= 1;
+                }
+                Instr::Return => {
+                    if pc != *end || *values != 1 {
+                        return false;
+                    }
+                    bodies.pop();
+                    match bodies.last_mut() {
+                        Some((values, _)) => *values += 1,
+                        None => return pc + 1 == self.code.len(),
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+impl From<&DeBruijn> for Program {
+    fn from(d: &DeBruijn) -> Program {
+        //steps left to do, visiting a lambda, adding an instruction, or setting the length of a closure once its body is added
+        enum Step<'a> {
+            Visit(&'a DeBruijn),
+            Emit(Instr),
+            Close(usize),
+        }
+        let mut code = Vec::new();
+        let mut stack = vec![Step::Emit(Instr::Return), Step::Visit(d)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(DeBruijn::Variable(i)) => code.push(Instr::Var(*i)),
+                Step::Visit(DeBruijn::Func(b)) => {
+                    stack.push(Step::Close(code.len()));
+                    stack.push(Step::Emit(Instr::Return));
+                    stack.push(Step::Visit(b));
+                    code.push(Instr::Closure(0));
+                }
+                Step::Visit(DeBruijn::Reducible((a, b))) => {
+                    stack.push(Step::Emit(Instr::Apply));
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Emit(i) => code.push(i),
+                Step::Close(pos) => code[pos] = Instr::Closure(code.len() - pos - 1),
+            }
+        }
+        Program { code }
+    }
+}
+
+//write a number 7 bits at a time, lowest bits first, with the top bit set on every byte but the last
+fn write_number(out: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        out.push((n as u8) | 0x80);
+        n >>= 7;
+    }
+    out.push(n as u8);
+}
+
+//read a number written by write_number
+fn read_number(bytes: &[u8], pos: &mut usize) -> Result<usize, DecodeError> {
+    let mut n: usize = 0;
+    let mut shift = 0;
+    loop {
+        let b = *bytes.get(*pos).ok_or(DecodeError::UnexpectedEnd)?;
+        *pos += 1;
+        if shift >= usize::BITS {
+            return Err(DecodeError::Malformed);
+        }
+        n |= ((b & 0x7f) as usize) << shift;
+        if b & 0x80 == 0 {
+            return Ok(n);
+        }
+        shift += 7;
+    }
+}
+
+///Virtual machine running a Program with call-by-value
+///
+///```rust
+///use easy_lambda_calculus::*;
+///use easy_lambda_calculus::bytecode::{Program, Vm};
+///
+///let p = Program::compile(&lambda!("(%x|y.x) (%z.z)"));
+///let mut vm = Vm::new(&p);
+///while vm.step() {}
+///println!("{}", vm.read_back());
+/// //outputs (%x|y.y)
+///```
+///
+///#### Machine method:
+///
+///The machine holds a stack of values, a stack of calls to return to, and the environment of the running function, variable 0 first.
+///Values are closures, the position of a function body together with the environment it was made in.
+///
+///An apply instruction adds the input to the environment of the function and jumps to its body, which counts as a single step of beta reduction.
+///Like a CekMachine, inputs are evaluated before they are applied and the machine never runs inside functions,
+///so it gives the same lambda as Strategy::CallByValue.
+pub struct Vm<'a> {
+    program: &'a Program,
+    pc: usize,
+    env: Env,
+    values: Vec<Rc<Value>>,
+    calls: Vec<(usize, Env)>,
+    steps: usize,
+    transitions: usize,
+}
+
+//a function body with the environment it was made in
+struct Value {
+    body: usize,
+    env: Env,
+}
+
+//linked list of values, the first being for variable 0
+type Env = Option<Rc<Frame>>;
+
+struct Frame {
+    value: Rc<Value>,
+    next: Env,
+}
+
+impl<'a> Vm<'a> {
+    ///Make a new machine to run a program
+    pub fn new(program: &'a Program) -> Vm<'a> {
+        Vm {
+            program,
+            pc: 0,
+            env: None,
+            values: Vec::new(),
+            calls: Vec::new(),
+            steps: 0,
+            transitions: 0,
+        }
+    }
+    ///Run a single instruction, returning false if the program has already stopped
+    pub fn step(&mut self) -> bool {
+        match self.program.code[self.pc] {
+            Instr::Var(i) => {
+                let mut env = &self.env;
+                for _ in 0..i {
+                    env = &env.as_ref().unwrap().next;
+                }
+                self.values.push(env.as_ref().unwrap().value.clone());
+                self.pc += 1;
+            }
+            Instr::Closure(n) => {
+                self.values.push(Rc::new(Value {
+                    body: self.pc + 1,
+                    env: self.env.clone(),
+                }));
+                self.pc += n + 1;
+            }
+            Instr::Apply => {
+                let input = self.values.pop().unwrap();
+                let f = self.values.pop().unwrap();
+                let env = Some(Rc::new(Frame {
+                    value: input,
+                    next: f.env.clone(),
+                }));
+                self.calls
+                    .push((self.pc + 1, std::mem::replace(&mut self.env, env)));
+                self.pc = f.body;
+                self.steps += 1;
+            }
+            Instr::Return => match self.calls.pop() {
+                Some((pc, env)) => {
+                    self.pc = pc;
+                    self.env = env;
+                }
+                None => return false,
+            },
+        }
+        self.transitions += 1;
+        true
+    }
+    ///Run the program until it stops
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+    ///Number of beta reduction steps done so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    ///Number of instructions run so far
+    pub fn transitions(&self) -> usize {
+        self.transitions
+    }
+    ///Read back the value the program stopped with as a lambda, giving every function a different variable name
+    ///
+    ///Panics if the program has not stopped yet.
+    pub fn read_back(&self) -> Lambda {
+        if self.program.code[self.pc] != Instr::Return || !self.calls.is_empty() {
+            panic!("Cannot read back, the program has not stopped");
+        }
+        //a function body being read, with the names of its functions and of the functions it is inside of
+        struct Reader<'b> {
+            pc: usize,
+            env: &'b Env,
+            bound: Vec<usize>,
+            open: Vec<usize>,
+        }
+        let code = &self.program.code;
+        let mut names = 0;
+        let value = self.values.last().unwrap();
+        let mut readers = vec![Reader {
+            pc: value.body,
+            env: &value.env,
+            bound: vec![names],
+            open: vec![names],
+        }];
+        names += 1;
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(r) = readers.last_mut() {
+            let i = code[r.pc];
+            r.pc += 1;
+            match i {
+                Instr::Var(i) if i < r.bound.len() => {
+                    out.push(Lambda::Variable(Lambda::get_name(
+                        r.bound[r.bound.len() - 1 - i],
+                    )));
+                }
+                Instr::Var(i) => {
+                    let mut env = r.env;
+                    for _ in 0..i - r.bound.len() {
+                        env = &env.as_ref().unwrap().next;
+                    }
+                    let v = &env.as_ref().unwrap().value;
+                    readers.push(Reader {
+                        pc: v.body,
+                        env: &v.env,
+                        bound: vec![names],
+                        open: vec![names],
+                    });
+                    names += 1;
+                }
+                Instr::Closure(_) => {
+                    r.bound.push(names);
+                    r.open.push(names);
+                    names += 1;
+                }
+                Instr::Apply => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(a.attach(b));
+                }
+                Instr::Return => {
+                    let n = r.open.pop().unwrap();
+                    r.bound.pop();
+                    let b = out.pop().unwrap();
+                    out.push(Lambda::func(Lambda::get_name(n), b));
+                    if r.open.is_empty() {
+                        readers.pop();
+                    }
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+}
+
+//implement display for programs, writing one instruction on every line
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in &self.code {
+            match i {
+                Instr::Var(n) => writeln!(f, "var {}", n)?,
+                Instr::Closure(n) => writeln!(f, "closure {}", n)?,
+                Instr::Apply => writeln!(f, "apply")?,
+                Instr::Return => writeln!(f, "return")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BadMagic => write!(f, "Not a compiled lambda"),
+            Self::Version(v) => write!(f, "Unknown bytecode version {}", v),
+            Self::BadOpcode(o) => write!(f, "Unknown opcode {}", o),
+            Self::UnexpectedEnd => write!(f, "Unexpected end of bytecode"),
+            Self::Malformed => write!(f, "Malformed bytecode"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+//implement drop with a stack, so dropping long chains of environments does not overflow the stack
+impl Drop for Frame {
+    fn drop(&mut self) {
+        let mut stack = vec![self.next.take()];
+        if let Some(v) = Rc::get_mut(&mut self.value) {
+            stack.push(v.env.take());
+        }
+        while let Some(env) = stack.pop() {
+            if let Some(rc) = env
+                && let Ok(mut f) = Rc::try_unwrap(rc)
+            {
+                stack.push(f.next.take());
+                if let Some(v) = Rc::get_mut(&mut f.value) {
+                    stack.push(v.env.take());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! CEK abstract machine for call-by-value evaluation
+
+use crate::debruijn::{Closure, bind, lookup, read_closure};
+use crate::{DeBruijn, Lambda};
+use std::fmt;
+
+///Abstract machine evaluating a de Bruijn lambda with call-by-value, with a state that can be inspected after every transition
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let d = DeBruijn::from(&lambda!("(%x.x) ((%y.y) (%z.z))"));
+///let mut m = CekMachine::new(&d);
+///println!("{}", m);
+///while m.step() {
+///    println!("{}", m);
+///}
+/// //outputs eval ((%.0) ((%.0) (%.0))) | [] | []
+/// //then eval (%.0) | [] | [([] ((%x.x) (%y.y)))]
+/// //then return (%.0) | [] | [([] ((%x.x) (%y.y)))]
+/// //then eval ((%.0) (%.0)) | [] | [((%x.x) [])]
+/// //...
+/// //then eval 0 | [(%x.x)] | []
+/// //then return (%.0) | [] | []
+///println!("{} in {} steps", m.read_back(), m.steps());
+/// //outputs (%x.x) in 2 steps
+///```
+///
+///#### Machine method:
+///
+///The state of the machine is made of the control, the environment and the continuation.
+///The control is either a lambda being evaluated, or a value being returned, values are always functions.
+///The environment holds the values of the variables in the control, variable 0 first.
+///The continuation holds what is left to do once the control is a value, innermost first.
+///
+///An application evaluates the function first and the input second, then the input is added to the environment of the function output, which counts as a single step of beta reduction.
+///Functions are values, so the machine never reduces inside them.
+///
+///The machine stops when a value is returned with nothing left in the continuation.
+///This is applicative order: inputs are evaluated once before being applied, but an input that never reaches a value stops the whole lambda from reaching one, even if the input is never used.
+pub struct CekMachine<'a> {
+    control: Control<'a>,
+    continuation: Vec<Frame<'a>>,
+    steps: usize,
+    transitions: usize,
+}
+
+//the control, a closure being evaluated or a value being returned
+enum Control<'a> {
+    Eval(Closure<'a>),
+    Return(Closure<'a>),
+}
+
+//a frame of the continuation, an input left to evaluate or a function left to apply
+enum Frame<'a> {
+    Input(Closure<'a>),
+    Apply(Closure<'a>),
+}
+
+///One frame of the continuation of a CekMachine, read back as a lambda
+#[derive(Debug, PartialEq, Clone)]
+pub enum Continuation {
+    ///Input of an application, evaluated once the function is a value, displayed as ([] input)
+    Input(Lambda),
+    ///Function of an application, applied once the input is a value, displayed as (function [])
+    Apply(Lambda),
+}
+
+impl<'a> CekMachine<'a> {
+    ///Make a new machine to evaluate a lambda
+    pub fn new(term: &'a DeBruijn) -> CekMachine<'a> {
+        CekMachine {
+            control: Control::Eval(Closure { term, env: None }),
+            continuation: Vec::new(),
+            steps: 0,
+            transitions: 0,
+        }
+    }
+    ///Do a single transition of the machine, returning false if it already stopped with a value
+    pub fn step(&mut self) -> bool {
+        match &self.control {
+            Control::Eval(c) => match c.term {
+                DeBruijn::Variable(i) => {
+                    self.control = Control::Return(lookup(&c.env, *i));
+                }
+                DeBruijn::Func(_) => self.control = Control::Return(c.clone()),
+                DeBruijn::Reducible((a, b)) => {
+                    let env = c.env.clone();
+                    self.continuation.push(Frame::Input(Closure {
+                        term: b,
+                        env: env.clone(),
+                    }));
+                    self.control = Control::Eval(Closure { term: a, env });
+                }
+            },
+            Control::Return(v) => match self.continuation.pop() {
+                Some(Frame::Input(c)) => {
+                    self.continuation.push(Frame::Apply(v.clone()));
+                    self.control = Control::Eval(c);
+                }
+                Some(Frame::Apply(f)) => match f.term {
+                    DeBruijn::Func(b) => {
+                        self.control = Control::Eval(Closure {
+                            term: b,
+                            env: bind(f.env, v.clone()),
+                        });
+                        self.steps += 1;
+                    }
+                    _ => panic!("Cannot apply {}", f.term),
+                },
+                None => return false,
+            },
+        }
+        self.transitions += 1;
+        true
+    }
+    ///Run the machine until it stops with a value
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+    ///Number of beta reduction steps done so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    ///Number of transitions done so far
+    pub fn transitions(&self) -> usize {
+        self.transitions
+    }
+    ///Check if the control is a value being returned, rather than a lambda being evaluated
+    pub fn is_value(&self) -> bool {
+        matches!(self.control, Control::Return(_))
+    }
+    ///The de Bruijn lambda in the control
+    pub fn control(&self) -> &DeBruijn {
+        self.closure().term
+    }
+    ///The values of the variables in the control, variable 0 first, read back as lambdas
+    pub fn environment(&self) -> Vec<Lambda> {
+        let mut out = Vec::new();
+        let mut env = &self.closure().env;
+        while let Some(f) = env {
+            out.push(read_closure(&f.closure.borrow()));
+            env = &f.next;
+        }
+        out
+    }
+    ///The frames of the continuation, innermost first, read back as lambdas
+    pub fn continuation(&self) -> Vec<Continuation> {
+        self.continuation
+            .iter()
+            .rev()
+            .map(|f| match f {
+                Frame::Input(c) => Continuation::Input(read_closure(c)),
+                Frame::Apply(c) => Continuation::Apply(read_closure(c)),
+            })
+            .collect()
+    }
+    ///Read back the current state of the machine as a lambda
+    ///
+    ///The environment is substituted into the control, which is then placed inside every frame of the continuation.
+    ///Once the machine has stopped this is the value of the lambda.
+    pub fn read_back(&self) -> Lambda {
+        let mut out = read_closure(self.closure());
+        for f in self.continuation.iter().rev() {
+            out = match f {
+                Frame::Input(c) => out.attach(read_closure(c)),
+                Frame::Apply(c) => read_closure(c).attach(out),
+            };
+        }
+        out
+    }
+    //the closure in the control
+    fn closure(&self) -> &Closure<'a> {
+        match &self.control {
+            Control::Eval(c) | Control::Return(c) => c,
+        }
+    }
+}
+
+//implement display for the machine state, as control | environment | continuation
+impl fmt::Display for CekMachine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = if self.is_value() { "return" } else { "eval" };
+        let env: Vec<String> = self.environment().iter().map(|l| l.to_string()).collect();
+        let cont: Vec<String> = self.continuation().iter().map(|k| k.to_string()).collect();
+        write!(
+            f,
+            "{} {} | [{}] | [{}]",
+            kind,
+            self.control(),
+            env.join(", "),
+            cont.join(", ")
+        )
+    }
+}
+
+//implement display for continuation frames, with [] where the control goes
+impl fmt::Display for Continuation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Continuation::Input(l) => write!(f, "([] {})", l),
+            Continuation::Apply(l) => write!(f, "({} [])", l),
+        }
+    }
+}
@@ -0,0 +1,388 @@
+//! Code generation of standalone Rust, JavaScript and C programs from lambdas
+
+use crate::{DeBruijn, Lambda};
+use std::collections::BTreeSet;
+
+impl Lambda {
+    ///Generate a standalone Rust program running the lambda with closures
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("%x|y.x").emit_rust());
+    /// //outputs //generated by easy_lambda_calculus from (%x|y.x)
+    /// //...
+    /// //fn term() -> V {
+    /// //    V::F(Rc::new(move |v0: V| { let v0 = v0.clone(); V::F(Rc::new(move |_: V| v0.clone())) }))
+    /// //}
+    /// //...
+    ///```
+    ///
+    ///#### Generated programs:
+    ///
+    ///Every function is turned into a closure of the target language and every reducible into a call of app(), see Lambda.emit_c() for how C closures are made.
+    ///Inputs are evaluated before they are applied, like Strategy::CallByValue.
+    ///
+    ///The main function of the program evaluates the lambda and prints it decoded as a Church numeral or boolean:
+    ///it applies the lambda to a successor function and 0, and to two markers for true and false.
+    ///It prints the number, true or false, or `0 or false` for (%x|y.y) which is both, and `function` if the lambda is neither.
+    ///
+    ///The lambda must be closed and have no constants, generating a program from a lambda with free variables or constants panics.
+    pub fn emit_rust(&self) -> String {
+        let d = self.codegen_term();
+        let mut out = format!(
+            "//generated by easy_lambda_calculus from {}\n\n{}",
+            self, RUST_PRELUDE
+        );
+        out.push_str("fn term() -> V {\n    ");
+        Self::rust_expr(&d, 0, &mut out);
+        out.push_str("\n}\n\n");
+        out.push_str(RUST_MAIN);
+        out
+    }
+    ///Generate a standalone JavaScript program running the lambda with closures, see Lambda.emit_rust()
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("(%x.x) (%y.y)").emit_js());
+    /// //outputs //generated by easy_lambda_calculus from ((%x.x) (%y.y))
+    /// //...
+    /// //const term = app((v0 => v0), (v0 => v0));
+    /// //...
+    ///```
+    pub fn emit_js(&self) -> String {
+        let d = self.codegen_term();
+        let mut out = format!(
+            "//generated by easy_lambda_calculus from {}\n\n{}",
+            self, JS_PRELUDE
+        );
+        out.push_str("const term = ");
+        Self::js_expr(&d, 0, &mut out);
+        out.push_str(";\n\n");
+        out.push_str(JS_MAIN);
+        out
+    }
+    ///Generate a standalone C program running the lambda with closures, see Lambda.emit_rust()
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("%x|y.x").emit_c());
+    /// //outputs //generated by easy_lambda_calculus from (%x|y.x)
+    /// //...
+    /// //static V *f0(V *self, V *x);
+    /// //static V *f1(V *self, V *x);
+    /// //
+    /// //static V *f0(V *self, V *x) {
+    /// //    (void)self;
+    /// //    return mk(f1, 1, (V *[]){x});
+    /// //}
+    /// //...
+    ///```
+    ///
+    ///C has no closures, so every function is turned into a C function taking the closure and its input.
+    ///A closure holds the C function and an array of the variables its function uses from outside of it.
+    ///Values are never freed, as the program stops once the lambda is printed.
+    pub fn emit_c(&self) -> String {
+        let d = self.codegen_term();
+        let mut funcs: Vec<String> = Vec::new();
+        let mut term = String::new();
+        Self::c_expr(&d, &Scope::Top, &mut funcs, &mut term);
+        let mut out = format!(
+            "//generated by easy_lambda_calculus from {}\n\n{}",
+            self, C_PRELUDE
+        );
+        for i in 0..funcs.len() {
+            out.push_str(&format!("static V *f{}(V *self, V *x);\n", i));
+        }
+        out.push('\n');
+        for f in funcs {
+            out.push_str(&f);
+        }
+        out.push_str(&format!(
+            "static V *term(void) {{\n    return {};\n}}\n\n",
+            term
+        ));
+        out.push_str(C_MAIN);
+        out
+    }
+    //convert the lambda for code generation, panicking if it has free variables
+    fn codegen_term(&self) -> DeBruijn {
+        self.check_closed();
+        DeBruijn::from(self)
+    }
+    //recursive function to write a lambda as a Rust expression, with the number of functions it is inside of
+    //variables are named after the number of functions outside of the function binding them
+    fn rust_expr(d: &DeBruijn, depth: usize, out: &mut String) {
+        match d {
+            DeBruijn::Variable(i) => out.push_str(&format!("v{}.clone()", depth - 1 - i)),
+            DeBruijn::Func(b) => {
+                //the closures are Fn, so the variables they use from outside are cloned to be moved into them
+                let free = free_levels(d, depth);
+                if !free.is_empty() {
+                    out.push_str("{ ");
+                }
+                for l in &free {
+                    out.push_str(&format!("let v{} = v{}.clone(); ", l, l));
+                }
+                if free_levels(b, depth + 1).contains(&depth) {
+                    out.push_str(&format!("V::F(Rc::new(move |v{}: V| ", depth));
+                } else {
+                    out.push_str("V::F(Rc::new(move |_: V| ");
+                }
+                Self::rust_expr(b, depth + 1, out);
+                out.push_str("))");
+                if !free.is_empty() {
+                    out.push_str(" }");
+                }
+            }
+            DeBruijn::Reducible((a, b)) => {
+                out.push_str("app(");
+                Self::rust_expr(a, depth, out);
+                out.push_str(", ");
+                Self::rust_expr(b, depth, out);
+                out.push(')');
+            }
+        }
+    }
+    //recursive function to write a lambda as a JavaScript expression, naming variables like Lambda.rust_expr()
+    fn js_expr(d: &DeBruijn, depth: usize, out: &mut String) {
+        match d {
+            DeBruijn::Variable(i) => out.push_str(&format!("v{}", depth - 1 - i)),
+            DeBruijn::Func(b) => {
+                out.push_str(&format!("(v{} => ", depth));
+                Self::js_expr(b, depth + 1, out);
+                out.push(')');
+            }
+            DeBruijn::Reducible((a, b)) => {
+                out.push_str("app(");
+                Self::js_expr(a, depth, out);
+                out.push_str(", ");
+                Self::js_expr(b, depth, out);
+                out.push(')');
+            }
+        }
+    }
+    //recursive function to write a lambda as a C expression, adding a C function for every function of the lambda
+    fn c_expr(d: &DeBruijn, scope: &Scope, funcs: &mut Vec<String>, out: &mut String) {
+        match d {
+            DeBruijn::Variable(i) => out.push_str(&scope.c_variable(scope.depth() - 1 - i)),
+            DeBruijn::Func(b) => {
+                let depth = scope.depth();
+                let captured: Vec<usize> = free_levels(d, depth).into_iter().collect();
+                let n = funcs.len();
+                funcs.push(String::new());
+                let mut body = String::new();
+                let inner = Scope::Func {
+                    depth: depth + 1,
+                    captured: captured.clone(),
+                };
+                Self::c_expr(b, &inner, funcs, &mut body);
+                //mark the closure and input as used when they are not, so compilers do not warn about them
+                let mut unused = String::new();
+                if captured.is_empty() {
+                    unused.push_str("    (void)self;\n");
+                }
+                if !free_levels(b, depth + 1).contains(&depth) {
+                    unused.push_str("    (void)x;\n");
+                }
+                funcs[n] = format!(
+                    "static V *f{}(V *self, V *x) {{\n{}    return {};\n}}\n\n",
+                    n, unused, body
+                );
+                if captured.is_empty() {
+                    out.push_str(&format!("mk(f{}, 0, NULL)", n));
+                } else {
+                    let env: Vec<String> = captured.iter().map(|l| scope.c_variable(*l)).collect();
+                    out.push_str(&format!(
+                        "mk(f{}, {}, (V *[]){{{}}})",
+                        n,
+                        captured.len(),
+                        env.join(", ")
+                    ));
+                }
+            }
+            DeBruijn::Reducible((a, b)) => {
+                out.push_str("app(");
+                Self::c_expr(a, scope, funcs, out);
+                out.push_str(", ");
+                Self::c_expr(b, scope, funcs, out);
+                out.push(')');
+            }
+        }
+    }
+}
+
+//the C function a lambda is written in, the term function or the function of a lambda with the variables its closure holds
+enum Scope {
+    Top,
+    Func { depth: usize, captured: Vec<usize> },
+}
+
+impl Scope {
+    //number of functions outside of the lambda
+    fn depth(&self) -> usize {
+        match self {
+            Scope::Top => 0,
+            Scope::Func { depth, .. } => *depth,
+        }
+    }
+    //C expression for the variable bound by the function with the given number of functions outside of it
+    fn c_variable(&self, level: usize) -> String {
+        match self {
+            Scope::Top => panic!("Cannot generate code, unbound variable"),
+            Scope::Func { depth, captured } => {
+                if level + 1 == *depth {
+                    "x".to_string()
+                } else {
+                    match captured.iter().position(|l| *l == level) {
+                        Some(k) => format!("self->env[{}]", k),
+                        None => panic!("Cannot generate code, unbound variable"),
+                    }
+                }
+            }
+        }
+    }
+}
+
+//find the variables used in a lambda that are bound outside of it, by the number of functions outside of their function
+fn free_levels(d: &DeBruijn, depth: usize) -> BTreeSet<usize> {
+    let mut out = BTreeSet::new();
+    //lambdas left to visit, with the number of functions entered since the start
+    let mut stack = vec![(d, 0)];
+    while let Some((d, inside)) = stack.pop() {
+        match d {
+            DeBruijn::Variable(i) => {
+                if *i >= inside {
+                    out.insert(depth + inside - 1 - i);
+                }
+            }
+            DeBruijn::Func(b) => stack.push((b, inside + 1)),
+            DeBruijn::Reducible((a, b)) => {
+                stack.push((a, inside));
+                stack.push((b, inside));
+            }
+        }
+    }
+    out
+}
+
+const RUST_PRELUDE: &str = "use std::rc::Rc;
+
+//a value is a closure, a number or marker used to decode the lambda, or stuck after applying something that is not a closure
+#[derive(Clone)]
+enum V {
+    F(Rc<dyn Fn(V) -> V>),
+    Count(u64),
+    Mark(bool),
+    Stuck,
+}
+
+fn app(f: V, x: V) -> V {
+    match f {
+        V::F(f) => f(x),
+        _ => V::Stuck,
+    }
+}
+
+";
+
+const RUST_MAIN: &str = "fn main() {
+    let t = term();
+    let succ = V::F(Rc::new(|x| match x {
+        V::Count(n) => V::Count(n + 1),
+        _ => V::Stuck,
+    }));
+    let n = app(app(t.clone(), succ), V::Count(0));
+    let b = app(app(t, V::Mark(true)), V::Mark(false));
+    match (n, b) {
+        (V::Count(n), V::Mark(b)) => println!(\"{} or {}\", n, b),
+        (V::Count(n), _) => println!(\"{}\", n),
+        (_, V::Mark(b)) => println!(\"{}\", b),
+        _ => println!(\"function\"),
+    }
+}
+";
+
+const JS_PRELUDE: &str = "//a value is a closure, a number or boolean used to decode the lambda, or stuck after applying something that is not a closure
+const STUCK = { stuck: true };
+const app = (f, x) => (typeof f === \"function\" ? f(x) : STUCK);
+
+";
+
+const JS_MAIN: &str =
+    "const n = app(app(term, (x) => (typeof x === \"number\" ? x + 1 : STUCK)), 0);
+const b = app(app(term, true), false);
+if (typeof n === \"number\" && typeof b === \"boolean\") {
+    console.log(n + \" or \" + b);
+} else if (typeof n === \"number\") {
+    console.log(String(n));
+} else if (typeof b === \"boolean\") {
+    console.log(String(b));
+} else {
+    console.log(\"function\");
+}
+";
+
+const C_PRELUDE: &str = "#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+//a value is a closure, a number or marker used to decode the lambda, or stuck after applying something that is not a closure
+enum Kind { CLOSURE, COUNT, MARK, STUCK };
+
+typedef struct V V;
+struct V {
+    enum Kind kind;
+    unsigned long long n;
+    V *(*code)(V *self, V *x);
+    V **env;
+};
+
+static V *value(enum Kind kind, unsigned long long n) {
+    V *v = malloc(sizeof(V));
+    v->kind = kind;
+    v->n = n;
+    v->code = NULL;
+    v->env = NULL;
+    return v;
+}
+
+static V *mk(V *(*code)(V *self, V *x), size_t len, V **env) {
+    V *v = value(CLOSURE, 0);
+    v->code = code;
+    if (len > 0) {
+        v->env = malloc(len * sizeof(V *));
+        memcpy(v->env, env, len * sizeof(V *));
+    }
+    return v;
+}
+
+static V *app(V *f, V *x) {
+    return f->kind == CLOSURE ? f->code(f, x) : value(STUCK, 0);
+}
+
+static V *succ(V *self, V *x) {
+    (void)self;
+    return x->kind == COUNT ? value(COUNT, x->n + 1) : value(STUCK, 0);
+}
+
+";
+
+const C_MAIN: &str = "int main(void) {
+    V *t = term();
+    V *n = app(app(t, mk(succ, 0, NULL)), value(COUNT, 0));
+    V *b = app(app(t, value(MARK, 1)), value(MARK, 0));
+    if (n->kind == COUNT && b->kind == MARK) {
+        printf(\"%llu or %s\\n\", n->n, b->n ? \"true\" : \"false\");
+    } else if (n->kind == COUNT) {
+        printf(\"%llu\\n\", n->n);
+    } else if (b->kind == MARK) {
+        printf(\"%s\\n\", b->n ? \"true\" : \"false\");
+    } else {
+        printf(\"function\\n\");
+    }
+    return 0;
+}
+";
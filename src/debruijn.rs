@@ -0,0 +1,301 @@
+//! Lambdas with de Bruijn indices, used by the abstract machines
+
+use crate::{Lambda, Symbol};
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+///Lambda with every variable replaced by the number of functions between it and the function binding it
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let d = DeBruijn::from(&lambda!("%x|y.(x y)"));
+///println!("{}", d);
+/// //outputs (%.(%.(1 0)))
+///println!("{}", d.to_lambda());
+/// //outputs (%x|y.(x y))
+///```
+///
+///Sections marked for alpha reduction have their own variables, like in Lambda.free_vars().
+///Converting a lambda with free variables or constants panics.
+#[derive(Debug, PartialEq)]
+pub enum DeBruijn {
+    ///Variable, with the number of functions between it and the function binding it
+    Variable(usize),
+    ///Function
+    Func(Box<DeBruijn>),
+    ///Marks a lambda being applied into a function
+    Reducible((Box<DeBruijn>, Box<DeBruijn>)),
+}
+
+impl From<&Lambda> for DeBruijn {
+    fn from(l: &Lambda) -> DeBruijn {
+        //steps left to do, visiting a lambda, leaving a function, or building from the finished lambdas
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Unbind,
+            Restore(Vec<Symbol>),
+            Func,
+            Reducible,
+        }
+        let mut bound: Vec<Symbol> = Vec::new();
+        let mut stack = vec![Step::Visit(l)];
+        let mut out: Vec<DeBruijn> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Lambda::Variable(a)) => match bound.iter().rev().position(|v| v == a) {
+                    Some(i) => out.push(DeBruijn::Variable(i)),
+                    None => panic!("Cannot convert, free variable {}", a),
+                },
+                Step::Visit(Lambda::Func((a, b))) => match &**a {
+                    Lambda::Variable(c) => {
+                        bound.push(*c);
+                        stack.push(Step::Func);
+                        stack.push(Step::Unbind);
+                        stack.push(Step::Visit(b));
+                    }
+                    _ => panic!("Cannot convert {:?}", a),
+                },
+                Step::Visit(Lambda::Reducible((a, b))) => {
+                    stack.push(Step::Reducible);
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::AlphaMark(a)) => {
+                    stack.push(Step::Restore(std::mem::take(&mut bound)));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::Constant(c)) => {
+                    panic!(
+                        "Cannot convert constant {}, only Strategy::Substitution evaluates constants",
+                        c
+                    )
+                }
+                Step::Visit(l) => panic!("Cannot convert {:?}", l),
+                Step::Unbind => {
+                    bound.pop();
+                }
+                Step::Restore(b) => bound = b,
+                Step::Func => {
+                    let b = out.pop().unwrap();
+                    out.push(DeBruijn::Func(Box::new(b)));
+                }
+                Step::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(DeBruijn::Reducible((Box::new(a), Box::new(b))));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+}
+
+impl DeBruijn {
+    ///Turn back into a lambda, naming every function variable after how many functions it is inside of
+    ///
+    ///Function variables are named with the naming scheme of Lambda.alpha_reduce(): x, y, z, w, a, b ...
+    pub fn to_lambda(&self) -> Lambda {
+        //steps left to do, visiting a lambda with the number of functions around it, or building from the finished lambdas
+        enum Step<'a> {
+            Visit(&'a DeBruijn, usize),
+            Func(usize),
+            Reducible,
+        }
+        let mut stack = vec![Step::Visit(self, 0)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Self::Variable(i), depth) => {
+                    if *i >= depth {
+                        panic!("Cannot convert, free variable {}", i);
+                    }
+                    out.push(Lambda::Variable(Lambda::get_name(depth - 1 - i)));
+                }
+                Step::Visit(Self::Func(b), depth) => {
+                    stack.push(Step::Func(depth));
+                    stack.push(Step::Visit(b, depth + 1));
+                }
+                Step::Visit(Self::Reducible((a, b)), depth) => {
+                    stack.push(Step::Reducible);
+                    stack.push(Step::Visit(b, depth));
+                    stack.push(Step::Visit(a, depth));
+                }
+                Step::Func(depth) => {
+                    let b = out.pop().unwrap();
+                    out.push(Lambda::func(Lambda::get_name(depth), b));
+                }
+                Step::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(a.attach(b));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+    //take the lambdas inside this one, leaving variables in their place
+    fn take_inside(&mut self, stack: &mut Vec<DeBruijn>) {
+        match self {
+            Self::Func(a) => stack.push(std::mem::replace(&mut **a, Self::Variable(0))),
+            Self::Reducible((a, b)) => {
+                stack.push(std::mem::replace(&mut **a, Self::Variable(0)));
+                stack.push(std::mem::replace(&mut **b, Self::Variable(0)));
+            }
+            Self::Variable(_) => {}
+        }
+    }
+}
+
+//implement drop with a stack, so dropping deep lambdas does not overflow the stack
+impl Drop for DeBruijn {
+    fn drop(&mut self) {
+        let mut stack: Vec<DeBruijn> = Vec::new();
+        self.take_inside(&mut stack);
+        while let Some(mut d) = stack.pop() {
+            d.take_inside(&mut stack);
+        }
+    }
+}
+
+//implement display for de Bruijn lambdas, using a stack of lambdas and text left to write
+impl fmt::Display for DeBruijn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        enum Step<'a> {
+            Visit(&'a DeBruijn),
+            Text(&'static str),
+        }
+        let mut stack = vec![Step::Visit(self)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Text(t) => write!(f, "{}", t)?,
+                Step::Visit(DeBruijn::Variable(i)) => write!(f, "{}", i)?,
+                Step::Visit(DeBruijn::Func(b)) => {
+                    write!(f, "(%.")?;
+                    stack.push(Step::Text(")"));
+                    stack.push(Step::Visit(b));
+                }
+                Step::Visit(DeBruijn::Reducible((a, b))) => {
+                    write!(f, "(")?;
+                    stack.push(Step::Text(")"));
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Text(" "));
+                    stack.push(Step::Visit(a));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//a lambda together with the environment giving its free variables
+#[derive(Clone)]
+pub(crate) struct Closure<'a> {
+    pub(crate) term: &'a DeBruijn,
+    pub(crate) env: Env<'a>,
+}
+
+//linked list of closures, the first being for variable 0
+pub(crate) type Env<'a> = Option<Rc<Frame<'a>>>;
+
+//the closure in a frame can be replaced by its value, so every environment sharing the frame sees the update
+pub(crate) struct Frame<'a> {
+    pub(crate) closure: RefCell<Closure<'a>>,
+    pub(crate) next: Env<'a>,
+}
+
+//find the frame for variable i in an environment
+pub(crate) fn frame<'b, 'a>(env: &'b Env<'a>, i: usize) -> &'b Rc<Frame<'a>> {
+    let mut env = env;
+    for _ in 0..i {
+        env = &env.as_ref().unwrap().next;
+    }
+    env.as_ref().unwrap()
+}
+
+//find the closure for variable i in an environment
+pub(crate) fn lookup<'a>(env: &Env<'a>, i: usize) -> Closure<'a> {
+    frame(env, i).closure.borrow().clone()
+}
+
+//add a closure to the front of an environment
+pub(crate) fn bind<'a>(env: Env<'a>, closure: Closure<'a>) -> Env<'a> {
+    Some(Rc::new(Frame {
+        closure: RefCell::new(closure),
+        next: env,
+    }))
+}
+
+//turn a closure into a lambda by substituting its environment, giving every function a different variable name
+pub(crate) fn read_closure(c: &Closure) -> Lambda {
+    //steps left to do, visiting a lambda with its environment, leaving a function or a closure, or building from the finished lambdas
+    enum Step<'a> {
+        Visit(&'a DeBruijn, Env<'a>),
+        Unbind,
+        Restore(Vec<usize>),
+        Func(usize),
+        Reducible,
+    }
+    //names of the functions entered since the start of the current closure
+    let mut bound: Vec<usize> = Vec::new();
+    let mut names = 0;
+    let mut stack = vec![Step::Visit(c.term, c.env.clone())];
+    let mut out: Vec<Lambda> = Vec::new();
+    while let Some(step) = stack.pop() {
+        match step {
+            Step::Visit(DeBruijn::Variable(i), env) => {
+                if *i < bound.len() {
+                    out.push(Lambda::Variable(Lambda::get_name(
+                        bound[bound.len() - 1 - i],
+                    )));
+                } else {
+                    let c = lookup(&env, *i - bound.len());
+                    stack.push(Step::Restore(std::mem::take(&mut bound)));
+                    stack.push(Step::Visit(c.term, c.env.clone()));
+                }
+            }
+            Step::Visit(DeBruijn::Func(b), env) => {
+                bound.push(names);
+                stack.push(Step::Func(names));
+                stack.push(Step::Unbind);
+                stack.push(Step::Visit(b, env));
+                names += 1;
+            }
+            Step::Visit(DeBruijn::Reducible((a, b)), env) => {
+                stack.push(Step::Reducible);
+                stack.push(Step::Visit(b, env.clone()));
+                stack.push(Step::Visit(a, env));
+            }
+            Step::Unbind => {
+                bound.pop();
+            }
+            Step::Restore(b) => bound = b,
+            Step::Func(n) => {
+                let b = out.pop().unwrap();
+                out.push(Lambda::func(Lambda::get_name(n), b));
+            }
+            Step::Reducible => {
+                let b = out.pop().unwrap();
+                let a = out.pop().unwrap();
+                out.push(a.attach(b));
+            }
+        }
+    }
+    out.pop().unwrap()
+}
+
+//implement drop with a stack, so dropping long chains of environments does not overflow the stack
+impl Drop for Frame<'_> {
+    fn drop(&mut self) {
+        let mut stack = vec![self.next.take(), self.closure.get_mut().env.take()];
+        while let Some(env) = stack.pop() {
+            if let Some(rc) = env
+                && let Ok(mut f) = Rc::try_unwrap(rc)
+            {
+                stack.push(f.next.take());
+                stack.push(f.closure.get_mut().env.take());
+            }
+        }
+    }
+}
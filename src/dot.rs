@@ -0,0 +1,249 @@
+//! Graphviz DOT export of syntax trees and reduction graphs
+
+use crate::{Lambda, Symbol};
+use std::collections::HashMap;
+
+impl Lambda {
+    ///Render the syntax tree of the lambda in Graphviz DOT format
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("%x.(x y)").to_dot());
+    /// //outputs digraph lambda {
+    /// //    n0 [label="λx"];
+    /// //    n1 [label="@"];
+    /// //    n2 [label="x"];
+    /// //    n2 -> n0 [style=dashed, constraint=false];
+    /// //    n1 -> n2;
+    /// //    n3 [label="y"];
+    /// //    n1 -> n3;
+    /// //    n0 -> n1;
+    /// //}
+    ///```
+    ///
+    ///#### Graph layout:
+    ///
+    ///Functions are drawn as λx nodes with an edge to their output, applications as @ nodes with edges to the function and then the input,
+    ///and sections marked for alpha reduction as & nodes.
+    ///
+    ///Every bound variable has a dashed back-edge to the function binding it, free variables have none.
+    pub fn to_dot(&self) -> String {
+        let mut out = "digraph lambda {\n".to_string();
+        Self::dot_node(self, &mut vec![], &mut 0, &mut out);
+        out.push('}');
+        out
+    }
+    //recursive function to write the nodes and edges of a lambda, with a stack of the variables in scope and their nodes
+    fn dot_node(
+        l: &Lambda,
+        bound: &mut Vec<(Symbol, usize)>,
+        n: &mut usize,
+        out: &mut String,
+    ) -> usize {
+        let id = *n;
+        *n += 1;
+        match l {
+            Self::Variable(a) => {
+                out.push_str(&format!(
+                    "    n{} [label=\"{}\"];\n",
+                    id,
+                    escape(a.as_str())
+                ));
+                if let Some((_, b)) = bound.iter().rev().find(|(v, _)| v == a) {
+                    out.push_str(&format!(
+                        "    n{} -> n{} [style=dashed, constraint=false];\n",
+                        id, b
+                    ));
+                }
+            }
+            Self::Func((a, b)) => {
+                let v = match &**a {
+                    Self::Variable(v) => *v,
+                    _ => panic!("Cannot render function"),
+                };
+                out.push_str(&format!(
+                    "    n{} [label=\"λ{}\"];\n",
+                    id,
+                    escape(v.as_str())
+                ));
+                bound.push((v, id));
+                let c = Self::dot_node(b, bound, n, out);
+                bound.pop();
+                out.push_str(&format!("    n{} -> n{};\n", id, c));
+            }
+            Self::Reducible((a, b)) => {
+                out.push_str(&format!("    n{} [label=\"@\"];\n", id));
+                let c = Self::dot_node(a, bound, n, out);
+                out.push_str(&format!("    n{} -> n{};\n", id, c));
+                let d = Self::dot_node(b, bound, n, out);
+                out.push_str(&format!("    n{} -> n{};\n", id, d));
+            }
+            Self::Constant(c) => {
+                out.push_str(&format!(
+                    "    n{} [label=\"{}\"];\n",
+                    id,
+                    escape(&c.to_string())
+                ));
+            }
+            Self::AlphaMark(a) => {
+                out.push_str(&format!("    n{} [label=\"&\"];\n", id));
+                let c = Self::dot_node(a, &mut vec![], n, out);
+                out.push_str(&format!("    n{} -> n{};\n", id, c));
+            }
+            _ => panic!("Cannot render {:?}", l),
+        }
+        id
+    }
+    ///Render the beta reduction graph of the lambda in Graphviz DOT format
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("(%x|y.y) ((%z.z) (%z.z))").reduction_graph_dot(10));
+    /// //outputs digraph reductions {
+    /// //    n0 [label="((%x|y.y) ((%z.z) (%w.w)))", penwidth=2];
+    /// //    n0 -> n1;
+    /// //    n0 -> n2;
+    /// //    n1 [label="(%x.x)", peripheries=2];
+    /// //    n2 [label="((%x|y.y) (%z.z))"];
+    /// //    n2 -> n1;
+    /// //}
+    ///```
+    ///
+    ///#### Graph layout:
+    ///
+    ///Every node is a lambda, and every edge is a single step of beta reduction of any reducible in it, including ones inside functions,
+    ///or a delta rule of an operator applied to every input it takes, once the inputs it evaluates are constants it takes, see Constant.
+    ///Lambdas that only differ by the names of their variables are the same node, every function variable is given a different name in the labels.
+    ///
+    ///The starting lambda is drawn in bold and lambdas that cannot be reduced anymore are drawn with a double border.
+    ///
+    ///At most max_nodes lambdas are added to the graph, reductions of the last lambdas into new ones are left out.
+    ///
+    ///The lambda is alpha reduced before building the graph, see Lambda.alpha_reduce().
+    pub fn reduction_graph_dot(&self, max_nodes: usize) -> String {
+        let start = self.clone().alpha_reduce().rename_binders();
+        let mut out = "digraph reductions {\n".to_string();
+        let mut ids: HashMap<String, usize> = HashMap::new();
+        let mut queue = vec![start];
+        let mut i = 0;
+        ids.insert(Self::display(&queue[0]), 0);
+        while i < queue.len() {
+            let l = queue[i].clone();
+            let reducts = l.reducts();
+            let mut attrs = String::new();
+            if i == 0 {
+                attrs.push_str(", penwidth=2");
+            }
+            if reducts.is_empty() {
+                attrs.push_str(", peripheries=2");
+            }
+            out.push_str(&format!(
+                "    n{} [label=\"{}\"{}];\n",
+                i,
+                escape(&Self::display(&l)),
+                attrs
+            ));
+            for r in reducts {
+                let key = Self::display(&r);
+                let id = match ids.get(&key) {
+                    Some(id) => *id,
+                    None if queue.len() < max_nodes => {
+                        ids.insert(key, queue.len());
+                        queue.push(r);
+                        queue.len() - 1
+                    }
+                    None => continue,
+                };
+                out.push_str(&format!("    n{} -> n{};\n", i, id));
+            }
+            i += 1;
+        }
+        out.push('}');
+        out
+    }
+    //find every lambda reachable with a single step of beta reduction or a delta rule, with binders renamed uniquely
+    fn reducts(&self) -> Vec<Lambda> {
+        let mut out: Vec<Lambda> = Vec::new();
+        for r in Self::recursive_reducts(self) {
+            let r = r.rename_binders();
+            if !out.contains(&r) {
+                out.push(r);
+            }
+        }
+        out
+    }
+    //recursive function to contract every reducible and operator applied to its inputs of the lambda in turn
+    fn recursive_reducts(l: &Lambda) -> Vec<Lambda> {
+        match l {
+            Self::Variable(_) | Self::Constant(_) => vec![],
+            Self::Func((a, b)) => Self::recursive_reducts(b)
+                .into_iter()
+                .map(|d| Self::Func((a.clone(), Box::new(d))))
+                .collect(),
+            Self::Reducible((a, b)) => {
+                let mut out = Vec::new();
+                if let Self::Func((c, d)) = &**a {
+                    out.push(Self::recursive_reduce(*d.clone(), *c.clone(), *b.clone()));
+                }
+                if l.is_delta_step() {
+                    out.push(l.clone().delta());
+                }
+                for c in Self::recursive_reducts(a) {
+                    out.push(c.attach(*b.clone()));
+                }
+                for d in Self::recursive_reducts(b) {
+                    out.push(a.clone().attach(d));
+                }
+                out
+            }
+            _ => panic!("Cannot reduce {:?}", l),
+        }
+    }
+    //rename every function variable to a different name, so reducing never captures a variable
+    fn rename_binders(&self) -> Lambda {
+        let free: Vec<Symbol> = self.free_vars().iter().map(Symbol::from).collect();
+        Self::recursive_rename(self, &free, &mut vec![], &mut 0)
+    }
+    //recursive function to rename function variables, with a stack of the renamed variables in scope
+    fn recursive_rename(
+        l: &Lambda,
+        free: &[Symbol],
+        bound: &mut Vec<(Symbol, Symbol)>,
+        n: &mut usize,
+    ) -> Lambda {
+        match l {
+            Self::Variable(a) => match bound.iter().rev().find(|(v, _)| v == a) {
+                Some((_, b)) => Self::var(*b),
+                None => l.clone(),
+            },
+            Self::Func((a, b)) => {
+                let mut name = Self::get_name(*n);
+                *n += 1;
+                while free.contains(&name) {
+                    name = Self::get_name(*n);
+                    *n += 1;
+                }
+                match &**a {
+                    Self::Variable(v) => bound.push((*v, name)),
+                    _ => panic!("Cannot rename {:?}", l),
+                }
+                let out = Self::func(name, Self::recursive_rename(b, free, bound, n));
+                bound.pop();
+                out
+            }
+            Self::Reducible((a, b)) => {
+                let c = Self::recursive_rename(a, free, bound, n);
+                c.attach(Self::recursive_rename(b, free, bound, n))
+            }
+            Self::Constant(_) => l.clone(),
+            _ => panic!("Cannot rename {:?}", l),
+        }
+    }
+}
+
+//escape a string to be used inside a quoted DOT label
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
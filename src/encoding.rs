@@ -0,0 +1,776 @@
+//! Encoding Rust values as lambdas and decoding them back
+
+use crate::{Lambda, Strategy, Symbol};
+use std::fmt;
+
+///Rust value that can be encoded as a lambda
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///println!("{}", 3u64.into_lambda());
+/// //outputs (%f|x.(f (f (f x))))
+///println!("{}", (true, Some(1u64)).into_lambda());
+/// //outputs (%p.((p &(%x|y.x)) &(%n|s.(s &(%f|x.(f x))))))
+///```
+///
+///#### Encodings:
+///
+///bool is a Church boolean, (%x|y.x) for true and (%x|y.y) for false.
+///
+///u64 is a Church numeral, applying its first input n times to its second.
+///
+///(A, B) is a Church pair, applying its input to both values.
+///Values put inside other lambdas are marked for alpha reduction, so they keep their own variables when the lambda is evaluated.
+///
+///Option is (%n|s.n) for None and (%n|s.(s a)) for Some(a).
+///
+///Vec is a Church list, (%c|n.((c a) ((c b) n))) for [a, b], folding c over the values starting from n.
+pub trait IntoLambda {
+    ///Encode the value as a lambda
+    fn into_lambda(self) -> Lambda;
+}
+
+///Rust value that can be decoded from a lambda in normal form, see Lambda.decode()
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let add = lambda!("%m|n|f|x.((m f) ((n f) x))");
+///let n: u64 = lambda!("({} {}) {}", add, 2u64.into_lambda(), 3u64.into_lambda()).evaluate().decode().unwrap();
+///println!("{}", n);
+/// //outputs 5
+///```
+///
+///Every type decodes the same encoding IntoLambda gives it, except Vec which also decodes Scott lists:
+///(%n|c.n) for the empty list and (%n|c.((c a) t)) for a followed by the list t.
+pub trait FromLambda: Sized {
+    ///Decode a lambda in normal form
+    fn from_lambda(l: &Lambda) -> Result<Self, FromLambdaError>;
+}
+
+///Error found while decoding a lambda that does not encode the expected value
+#[derive(Debug, PartialEq, Clone)]
+pub struct FromLambdaError {
+    ///The encoding that was expected
+    pub expected: String,
+    ///The part of the lambda that does not match it, in normal form
+    pub found: Lambda,
+}
+
+impl FromLambdaError {
+    ///Make an error for a lambda that is not the expected encoding
+    pub fn new(expected: &str, found: &Lambda) -> FromLambdaError {
+        FromLambdaError {
+            expected: expected.to_string(),
+            found: found.clone().alpha_reduce(),
+        }
+    }
+}
+
+impl Lambda {
+    ///Decode the lambda into a Rust value, see FromLambda
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let pair: (bool, u64) = lambda!("%p.((p (%x|y.y)) (%f|x.(f x)))").decode().unwrap();
+    ///println!("{:?}", pair);
+    /// //outputs (false, 1)
+    ///println!("{}", lambda!("%x.x").decode::<bool>().unwrap_err());
+    /// //outputs Expected a Church boolean, found (%x.x)
+    ///```
+    ///
+    ///The lambda is first reduced to its normal form with Strategy::Nbe, so decoding a lambda that has none never stops.
+    ///The lambda must be closed, decoding a lambda with free variables panics.
+    pub fn decode<T: FromLambda>(&self) -> Result<T, FromLambdaError> {
+        T::from_lambda(&self.clone().evaluate_with(Strategy::Nbe))
+    }
+    ///Encode a constructor of a data type, with one function for every constructor
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let leaf = Lambda::constructor(2, 0, vec![], &[]);
+    ///let node = Lambda::constructor(2, 1, vec![leaf.clone(), 5u64.into_lambda(), leaf], &[true, false, true]);
+    ///println!("{}", node);
+    /// //outputs (%x|y.(((y ((&(%x|y.x) x) y)) &(%f|x.(f (f (f (f (f x))))))) ((&(%x|y.x) x) y)))
+    ///let (i, fields) = node.evaluate_with(Strategy::Nbe).constructor_fields(2).unwrap();
+    ///println!("{} {} {} {}", i, fields[0], fields[1], fields[2]);
+    /// //outputs 1 (%x|y.x) (%z|w.(z (z (z (z (z w)))))) (%x|y.x)
+    ///```
+    ///
+    ///Constructor index of a data type with n constructors is (%c0|...|cn.((c{index} field0) ... fieldm)), the Scott encoding.
+    ///Fields marked as recursive hold a value of the same data type, and are applied to the functions of the constructors first,
+    ///so the data type is Church encoded, every constructor function getting the fields with the data inside them already folded.
+    pub fn constructor(
+        constructors: usize,
+        index: usize,
+        fields: Vec<Lambda>,
+        recursive: &[bool],
+    ) -> Lambda {
+        let mut body = Lambda::Variable(Self::get_name(index));
+        for (i, f) in fields.into_iter().enumerate() {
+            let mut f = f.marked();
+            if recursive.get(i) == Some(&true) {
+                for c in 0..constructors {
+                    f = f.attach(Lambda::Variable(Self::get_name(c)));
+                }
+            }
+            body = body.attach(f);
+        }
+        for c in (0..constructors).rev() {
+            body = Lambda::func(Self::get_name(c), body);
+        }
+        body
+    }
+    ///Lambda building a constructor of a data type from its fields, encoded like Lambda::constructor()
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Lambda::constructor_function(2, 1, &[false, true]));
+    /// //outputs (%x|y|z|w.((w x) ((y z) w)))
+    ///```
+    pub fn constructor_function(constructors: usize, index: usize, recursive: &[bool]) -> Lambda {
+        let fields = recursive.len();
+        let mut body = Lambda::Variable(Self::get_name(fields + index));
+        for (i, r) in recursive.iter().enumerate() {
+            let mut f = Lambda::Variable(Self::get_name(i));
+            if *r {
+                for c in 0..constructors {
+                    f = f.attach(Lambda::Variable(Self::get_name(fields + c)));
+                }
+            }
+            body = body.attach(f);
+        }
+        for n in (0..fields + constructors).rev() {
+            body = Lambda::func(Self::get_name(n), body);
+        }
+        body
+    }
+    ///Lambda matching on a data type encoded like Lambda::constructor(), applying it to one function for every constructor
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Lambda::destructor(2));
+    /// //outputs (%x|y|z.((x y) z))
+    ///```
+    pub fn destructor(constructors: usize) -> Lambda {
+        let mut body = Lambda::Variable(Self::get_name(0));
+        for c in 0..constructors {
+            body = body.attach(Lambda::Variable(Self::get_name(c + 1)));
+        }
+        for n in (0..=constructors).rev() {
+            body = Lambda::func(Self::get_name(n), body);
+        }
+        body
+    }
+    ///Get the constructor index and fields of a lambda in normal form encoded like Lambda::constructor(), or None if it is not
+    ///
+    ///Recursive fields are given back as data of the same type, so they can be decoded the same way.
+    pub fn constructor_fields(&self, constructors: usize) -> Option<(usize, Vec<Lambda>)> {
+        let (vars, body) = self.funcs(constructors)?;
+        let mut fields = Vec::new();
+        let mut head = body;
+        while let Self::Reducible((a, b)) = head {
+            fields.push(&**b);
+            head = a;
+        }
+        fields.reverse();
+        let index = match head {
+            Self::Variable(a) => vars.iter().rposition(|v| v == a)?,
+            _ => return None,
+        };
+        //a field using the constructor functions is a recursive field already folded, so it is turned back into the data it was folded from
+        let fields = fields
+            .into_iter()
+            .map(|f| {
+                if f.is_closed() {
+                    f.clone()
+                } else {
+                    let mut f = f.clone();
+                    for v in vars.iter().rev() {
+                        f = Lambda::func(*v, f);
+                    }
+                    f
+                }
+            })
+            .collect();
+        Some((index, fields))
+    }
+    //get the input variables and output of a lambda that is n functions, or None if it is not
+    fn funcs(&self, n: usize) -> Option<(Vec<Symbol>, &Lambda)> {
+        let mut vars = Vec::new();
+        let mut l = self;
+        for _ in 0..n {
+            match l {
+                Self::Func((a, b)) => match &**a {
+                    Self::Variable(a) => {
+                        vars.push(*a);
+                        l = b;
+                    }
+                    _ => return None,
+                },
+                _ => return None,
+            }
+        }
+        Some((vars, l))
+    }
+    //mark the lambda for alpha reduction, so it keeps its own variables when it is put inside another lambda
+    fn marked(self) -> Lambda {
+        Lambda::AlphaMark(Box::new(self))
+    }
+    //check if the lambda is the variable
+    fn is_var(&self, a: Symbol) -> bool {
+        matches!(self, Self::Variable(b) if *b == a)
+    }
+    //get the input of a lambda applying the variable to a single input
+    fn applied(&self, f: Symbol) -> Option<&Lambda> {
+        match self {
+            Self::Reducible((a, b)) if a.is_var(f) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl IntoLambda for Lambda {
+    fn into_lambda(self) -> Lambda {
+        self
+    }
+}
+
+impl FromLambda for Lambda {
+    fn from_lambda(l: &Lambda) -> Result<Lambda, FromLambdaError> {
+        Ok(l.clone().alpha_reduce())
+    }
+}
+
+impl<T: IntoLambda> IntoLambda for Box<T> {
+    fn into_lambda(self) -> Lambda {
+        (*self).into_lambda()
+    }
+}
+
+impl<T: FromLambda> FromLambda for Box<T> {
+    fn from_lambda(l: &Lambda) -> Result<Box<T>, FromLambdaError> {
+        Ok(Box::new(T::from_lambda(l)?))
+    }
+}
+
+impl IntoLambda for bool {
+    fn into_lambda(self) -> Lambda {
+        if self {
+            Lambda::func("x", Lambda::func("y", Lambda::var("x")))
+        } else {
+            Lambda::func("x", Lambda::func("y", Lambda::var("y")))
+        }
+    }
+}
+
+impl FromLambda for bool {
+    fn from_lambda(l: &Lambda) -> Result<bool, FromLambdaError> {
+        match l.funcs(2) {
+            Some((v, b)) if b.is_var(v[1]) => Ok(false),
+            Some((v, b)) if b.is_var(v[0]) => Ok(true),
+            _ => Err(FromLambdaError::new("a Church boolean", l)),
+        }
+    }
+}
+
+impl IntoLambda for u64 {
+    fn into_lambda(self) -> Lambda {
+        let mut l = Lambda::var("x");
+        for _ in 0..self {
+            l = Lambda::var("f").attach(l);
+        }
+        Lambda::func("f", Lambda::func("x", l))
+    }
+}
+
+impl FromLambda for u64 {
+    fn from_lambda(l: &Lambda) -> Result<u64, FromLambdaError> {
+        if let Some((v, mut b)) = l.funcs(2) {
+            let mut n = 0;
+            while v[0] != v[1]
+                && let Some(c) = b.applied(v[0])
+            {
+                b = c;
+                n += 1;
+            }
+            if b.is_var(v[1]) {
+                return Ok(n);
+            }
+        }
+        Err(FromLambdaError::new("a Church numeral", l))
+    }
+}
+
+impl<A: IntoLambda, B: IntoLambda> IntoLambda for (A, B) {
+    fn into_lambda(self) -> Lambda {
+        let body = Lambda::var("p")
+            .attach(self.0.into_lambda().marked())
+            .attach(self.1.into_lambda().marked());
+        Lambda::func("p", body)
+    }
+}
+
+impl<A: FromLambda, B: FromLambda> FromLambda for (A, B) {
+    fn from_lambda(l: &Lambda) -> Result<(A, B), FromLambdaError> {
+        if let Some((v, Lambda::Reducible((a, b)))) = l.funcs(1)
+            && let Some(a) = a.applied(v[0])
+            && a.is_closed()
+            && b.is_closed()
+        {
+            return Ok((A::from_lambda(a)?, B::from_lambda(b)?));
+        }
+        Err(FromLambdaError::new("a Church pair", l))
+    }
+}
+
+impl<T: IntoLambda> IntoLambda for Option<T> {
+    fn into_lambda(self) -> Lambda {
+        let body = match self {
+            Some(a) => Lambda::var("s").attach(a.into_lambda().marked()),
+            None => Lambda::var("n"),
+        };
+        Lambda::func("n", Lambda::func("s", body))
+    }
+}
+
+impl<T: FromLambda> FromLambda for Option<T> {
+    fn from_lambda(l: &Lambda) -> Result<Option<T>, FromLambdaError> {
+        match l.funcs(2) {
+            Some((v, b)) if v[0] != v[1] && b.is_var(v[0]) => Ok(None),
+            Some((v, b)) => match b.applied(v[1]) {
+                Some(a) if a.is_closed() => Ok(Some(T::from_lambda(a)?)),
+                _ => Err(FromLambdaError::new("an option", l)),
+            },
+            None => Err(FromLambdaError::new("an option", l)),
+        }
+    }
+}
+
+impl<T: IntoLambda> IntoLambda for Vec<T> {
+    fn into_lambda(self) -> Lambda {
+        let mut l = Lambda::var("n");
+        for a in self.into_iter().rev() {
+            l = Lambda::var("c").attach(a.into_lambda().marked()).attach(l);
+        }
+        Lambda::func("c", Lambda::func("n", l))
+    }
+}
+
+impl<T: FromLambda> FromLambda for Vec<T> {
+    fn from_lambda(l: &Lambda) -> Result<Vec<T>, FromLambdaError> {
+        let mut out = Vec::new();
+        let mut l = l;
+        //the Scott list left to decode, one function pair at a time
+        while let Some((v, b)) = l.funcs(2) {
+            if v[0] == v[1] {
+                break;
+            }
+            //the Church list is the values applied to the first input, ending with the second
+            let mut b = b;
+            let mut church = false;
+            while let Lambda::Reducible((h, t)) = b
+                && let Some(h) = h.applied(v[0])
+                && h.is_closed()
+            {
+                out.push(T::from_lambda(h)?);
+                b = t;
+                church = true;
+            }
+            if b.is_var(v[1]) {
+                return Ok(out);
+            }
+            if !church {
+                if b.is_var(v[0]) {
+                    return Ok(out);
+                }
+                if let Lambda::Reducible((h, t)) = b
+                    && let Some(h) = h.applied(v[1])
+                    && h.is_closed()
+                    && t.is_closed()
+                {
+                    out.push(T::from_lambda(h)?);
+                    l = t;
+                    continue;
+                }
+            }
+            break;
+        }
+        Err(FromLambdaError::new("a Church or Scott list", l))
+    }
+}
+
+///Encoding of natural numbers as lambdas, with the lambdas to compute on them
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let n = Numeral::Scott;
+///println!("{}", n.encode(2));
+/// //outputs (%z|s.(s &(%z|s.(s &(%z|s.z)))))
+///let sum = lambda!("({} &{}) &{}", n.add(), n.encode(3), n.encode(4));
+///println!("{}", n.decode(&sum).unwrap());
+/// //outputs 7
+///let binary = lambda!("{} {}", n.convert(Numeral::Binary), sum);
+///println!("{}", Numeral::Binary.decode(&binary).unwrap());
+/// //outputs 7
+///```
+///
+///Every encoding has lambdas for zero, succ, pred, add, is_zero and case analysis, with pred of zero being zero and is_zero giving a Church boolean.
+///Lambdas that need recursion use the Y combinator, so they must be evaluated with a strategy that only evaluates inputs when they are used.
+///
+///#### Encodings:
+///
+///Church numerals apply their first input n times to their second, the same encoding IntoLambda gives u64.
+///Recursion comes for free, but finding the predecessor takes n steps.
+///
+///Scott numerals are (%z|s.z) for zero and (%z|s.(s p)) for the successor of p, so the predecessor takes a single step,
+///but recursing over them needs the Y combinator.
+///
+///Parigot numerals are (%z|s.z) for zero and (%z|s.((s p) ((p z) s))) for the successor of p, giving both the predecessor in a single step and recursion for free.
+///The lambdas double in size with every successor.
+///
+///Binary numerals are lists of bits starting from the lowest one, (%e|o|i.e) for zero, (%e|o|i.(o m)) for 2m and (%e|o|i.(i m)) for 2m + 1,
+///so they grow with the number of bits rather than with the number.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Numeral {
+    ///Church numeral, (%f|x.(f (f x))) for 2
+    Church,
+    ///Scott numeral, (%z|s.(s &(%z|s.(s &(%z|s.z))))) for 2
+    Scott,
+    ///Parigot numeral, (%z|s.((s p) ((p z) s))) with p being 1 for 2
+    Parigot,
+    ///Binary numeral, (%e|o|i.(o &(%e|o|i.(i &(%e|o|i.e))))) for 2
+    Binary,
+}
+
+impl Numeral {
+    ///Encode a number
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Numeral::Binary.encode(6));
+    /// //outputs (%e|o|i.(o &(%e|o|i.(i &(%e|o|i.(i &(%e|o|i.e)))))))
+    ///```
+    pub fn encode(self, n: u64) -> Lambda {
+        match self {
+            Self::Church => n.into_lambda(),
+            Self::Scott => {
+                let mut l = self.zero();
+                for _ in 0..n {
+                    l = Lambda::new("%z|s.(s &{})", vec![l]);
+                }
+                l
+            }
+            Self::Parigot => {
+                let mut l = self.zero();
+                for _ in 0..n {
+                    l = Lambda::new("%z|s.((s &{}) ((&{} z) s))", vec![l.clone(), l]);
+                }
+                l
+            }
+            Self::Binary => {
+                let mut l = self.zero();
+                for i in (0..u64::BITS - n.leading_zeros()).rev() {
+                    let bit = if n >> i & 1 == 1 { "i" } else { "o" };
+                    l = Lambda::func(
+                        "e",
+                        Lambda::func("o", Lambda::func("i", Lambda::var(bit).attach(l.marked()))),
+                    );
+                }
+                l
+            }
+        }
+    }
+    ///Lambda for zero
+    pub fn zero(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%f|x.x", vec![]),
+            Self::Scott | Self::Parigot => Lambda::new("%z|s.z", vec![]),
+            Self::Binary => Lambda::new("%e|o|i.e", vec![]),
+        }
+    }
+    ///Lambda giving the successor of its input
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Numeral::Parigot.succ());
+    /// //outputs (%n|z|s.((s n) ((n z) s)))
+    ///```
+    pub fn succ(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%n|f|x.(f ((n f) x))", vec![]),
+            Self::Scott => Lambda::new("%n|z|s.(s n)", vec![]),
+            Self::Parigot => Lambda::new("%n|z|s.((s n) ((n z) s))", vec![]),
+            Self::Binary => Lambda::new(
+                "&{} (%r|n.(((n &{}) (%m|e|o|i.(i m))) (%m|e|o|i.(o (r m)))))",
+                vec![fix(), Lambda::new("%e|o|i.(i &(%e|o|i.e))", vec![])],
+            ),
+        }
+    }
+    ///Lambda giving the predecessor of its input, or zero for zero
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let n = Numeral::Church;
+    ///println!("{}", n.decode(&lambda!("{} &{}", n.pred(), n.encode(5))).unwrap());
+    /// //outputs 4
+    ///```
+    pub fn pred(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%n|f|x.(((n (%g|h.(h (g f)))) (%u.x)) (%u.u))", vec![]),
+            Self::Scott => Lambda::new("%n.((n &{}) (%p.p))", vec![self.zero()]),
+            Self::Parigot => Lambda::new("%n.((n &{}) (%p|r.p))", vec![self.zero()]),
+            Self::Binary => Lambda::new(
+                "&{} (%r|n.(((n &{}) (%m|e|o|i.(i (r m)))) (%m.(((m &{}) (%k|e|o|i.(o m))) (%k|e|o|i.(o m))))))",
+                vec![fix(), self.zero(), self.zero()],
+            ),
+        }
+    }
+    ///Lambda adding its two inputs
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let n = Numeral::Binary;
+    ///println!("{}", n.decode(&lambda!("({} &{}) &{}", n.add(), n.encode(11), n.encode(6))).unwrap());
+    /// //outputs 17
+    ///```
+    pub fn add(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%m|n|f|x.((m f) ((n f) x))", vec![]),
+            Self::Scott => Lambda::new(
+                "&{} (%r|m|n.((m n) (%p.(&{} ((r p) n)))))",
+                vec![fix(), self.succ()],
+            ),
+            Self::Parigot => Lambda::new("%m|n.((m n) (%p|r.(&{} r)))", vec![self.succ()]),
+            Self::Binary => Lambda::new(
+                "&{} (%r|m|n.(((m n) (%a.(((n m) (%b|e|o|i.(o ((r a) b)))) (%b|e|o|i.(i ((r a) b)))))) (%c.(((n m) (%d|e|o|i.(i ((r c) d)))) (%g|e|o|i.(o (&{} ((r c) g))))))))",
+                vec![fix(), self.succ()],
+            ),
+        }
+    }
+    ///Lambda giving a Church boolean, true if its input is zero
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let n = Numeral::Scott;
+    ///println!("{}", lambda!("{} &{}", n.is_zero(), n.encode(0)).evaluate());
+    /// //outputs (%x|y.x)
+    ///```
+    pub fn is_zero(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%n.((n (%a|x|y.y)) (%x|y.x))", vec![]),
+            Self::Scott => Lambda::new("%n.((n (%x|y.x)) (%p|x|y.y))", vec![]),
+            Self::Parigot => Lambda::new("%n.((n (%x|y.x)) (%p|r|x|y.y))", vec![]),
+            Self::Binary => Lambda::new("%n.(((n (%x|y.x)) (%m|x|y.y)) (%m|x|y.y))", vec![]),
+        }
+    }
+    ///Lambda for case analysis, taking a number, the result for zero and a function applied to the predecessor of any other number
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let n = Numeral::Parigot;
+    ///let double_pred = lambda!("(({} &{}) &{}) (%p.(({} p) p))", n.case(), n.encode(3), n.zero(), n.add());
+    ///println!("{}", n.decode(&double_pred).unwrap());
+    /// //outputs 4
+    ///```
+    pub fn case(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%n|z|s.((n (%a.(s (&{} n)))) z)", vec![self.pred()]),
+            Self::Scott => Lambda::new("%n|z|s.((n z) s)", vec![]),
+            Self::Parigot => Lambda::new("%n|z|s.((n z) (%p|r.(s p)))", vec![]),
+            Self::Binary => Lambda::new(
+                "%n|z|s.(((n z) (%m.(s (&{} n)))) (%m.(s (&{} n))))",
+                vec![self.pred(), self.pred()],
+            ),
+        }
+    }
+    ///Lambda converting a number in this encoding to the same number in another
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let to_church = Numeral::Parigot.convert(Numeral::Church);
+    ///println!("{}", lambda!("{} &{}", to_church, Numeral::Parigot.encode(3)).evaluate_with(Strategy::Nbe));
+    /// //outputs (%x|y.(x (x (x y))))
+    ///```
+    pub fn convert(self, to: Numeral) -> Lambda {
+        if self == to {
+            return Lambda::new("%n.n", vec![]);
+        }
+        match self {
+            Self::Church => Lambda::new("%n.((n &{}) &{})", vec![to.succ(), to.zero()]),
+            Self::Parigot => Lambda::new("%n.((n &{}) (%p|r.(&{} r)))", vec![to.zero(), to.succ()]),
+            Self::Scott => Lambda::new(
+                "&{} (%r|n.((n &{}) (%p.(&{} (r p)))))",
+                vec![fix(), to.zero(), to.succ()],
+            ),
+            //double the lower bits rather than counting down, so it takes a step for every bit
+            Self::Binary => {
+                let double = Lambda::new("%k.((&{} k) k)", vec![to.add()]);
+                Lambda::new(
+                    "&{} (%r|n.(((n &{}) (%m.(&{} (r m)))) (%m.(&{} (&{} (r m))))))",
+                    vec![fix(), to.zero(), double.clone(), to.succ(), double],
+                )
+            }
+        }
+    }
+    ///Decode a number, reducing the lambda to its normal form with Strategy::Nbe first like Lambda.decode()
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Numeral::Church.decode(&lambda!("%x.x")).unwrap_err());
+    /// //outputs Expected a Church numeral, found (%x.x)
+    ///```
+    pub fn decode(self, l: &Lambda) -> Result<u64, FromLambdaError> {
+        let l = l.clone().evaluate_with(Strategy::Nbe);
+        let n = match self {
+            Self::Church => return u64::from_lambda(&l),
+            Self::Scott => Self::decode_scott(&l),
+            Self::Parigot => Self::decode_parigot(&l),
+            Self::Binary => Self::decode_binary(&l),
+        };
+        n.ok_or_else(|| FromLambdaError::new(&format!("a {:?} numeral", self), &l))
+    }
+    //decode a Scott numeral in normal form, counting the successors until zero
+    fn decode_scott(mut l: &Lambda) -> Option<u64> {
+        let mut n = 0;
+        loop {
+            let (v, b) = l.funcs(2)?;
+            if v[0] == v[1] {
+                return None;
+            }
+            if b.is_var(v[0]) {
+                return Some(n);
+            }
+            l = b.applied(v[1]).filter(|p| p.is_closed())?;
+            n += 1;
+        }
+    }
+    //decode a Parigot numeral in normal form, following the predecessors until zero
+    fn decode_parigot(mut l: &Lambda) -> Option<u64> {
+        let mut n = 0;
+        loop {
+            let (v, b) = l.funcs(2)?;
+            if v[0] == v[1] {
+                return None;
+            }
+            if b.is_var(v[0]) {
+                return Some(n);
+            }
+            l = match b {
+                Lambda::Reducible((a, _)) => a.applied(v[1]).filter(|p| p.is_closed())?,
+                _ => return None,
+            };
+            n += 1;
+        }
+    }
+    //decode a binary numeral in normal form, reading the bits from the lowest one
+    fn decode_binary(mut l: &Lambda) -> Option<u64> {
+        let mut bits = Vec::new();
+        loop {
+            let (v, b) = l.funcs(3)?;
+            if v[0] == v[1] || v[0] == v[2] || v[1] == v[2] {
+                return None;
+            }
+            if b.is_var(v[0]) {
+                break;
+            }
+            let (bit, m) = match (b.applied(v[1]), b.applied(v[2])) {
+                (Some(m), _) => (0, m),
+                (_, Some(m)) => (1, m),
+                _ => return None,
+            };
+            if !m.is_closed() {
+                return None;
+            }
+            bits.push(bit);
+            l = m;
+        }
+        if bits.len() > u64::BITS as usize {
+            return None;
+        }
+        Some(bits.iter().rev().fold(0, |n, b| n << 1 | b))
+    }
+}
+
+///Encoding of lists as lambdas, used by the list syntax [a, b, c], see ParseOptions
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let list = ListEncoding::Scott;
+///println!("{}", list.encode(vec![lambda!("%x.x")]));
+/// //outputs (%n|c.((c &(%x.x)) &(%n|c.n)))
+///let l = lambda!("({} &{}) &{}", list.cons(), 2u64.into_lambda(), list.nil());
+///println!("{:?}", list.decode(&l).unwrap().len());
+/// //outputs 1
+///```
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ListEncoding {
+    ///Church list, (%c|n.((c a) ((c b) n))) for [a, b], the same encoding IntoLambda gives Vec
+    Church,
+    ///Scott list, (%n|c.((c a) &(%n|c.((c b) &(%n|c.n))))) for [a, b], the same encoding as a data List = Nil | Cons h t
+    Scott,
+}
+
+impl ListEncoding {
+    ///Encode a list of lambdas
+    pub fn encode(self, items: Vec<Lambda>) -> Lambda {
+        match self {
+            Self::Church => items.into_lambda(),
+            Self::Scott => {
+                let mut l = self.nil();
+                for a in items.into_iter().rev() {
+                    l = Lambda::new("%n|c.((c &{}) &{})", vec![a, l]);
+                }
+                l
+            }
+        }
+    }
+    ///Lambda for the empty list
+    pub fn nil(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%c|n.n", vec![]),
+            Self::Scott => Lambda::new("%n|c.n", vec![]),
+        }
+    }
+    ///Lambda putting its first input in front of the list given as its second input
+    pub fn cons(self) -> Lambda {
+        match self {
+            Self::Church => Lambda::new("%h|t|c|n.((c h) ((t c) n))", vec![]),
+            Self::Scott => Lambda::new("%h|t|n|c.((c h) t)", vec![]),
+        }
+    }
+    ///Decode a list into its items, reducing it to its normal form with Strategy::Nbe first
+    ///
+    ///Church and Scott lists are both decoded, whatever the encoding is.
+    pub fn decode(self, l: &Lambda) -> Result<Vec<Lambda>, FromLambdaError> {
+        Vec::<Lambda>::from_lambda(&l.clone().evaluate_with(Strategy::Nbe))
+    }
+}
+
+//the Y combinator, giving recursion to the lambdas that need it
+fn fix() -> Lambda {
+    Lambda::new("%f.((%x.(f (x x))) (%x.(f (x x))))", vec![])
+}
+
+impl fmt::Display for FromLambdaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for FromLambdaError {}
@@ -0,0 +1,505 @@
+//! Named definitions and native Rust functions to evaluate lambdas with
+
+use crate::sugar::Sugar;
+use crate::{Constant, Lambda, Symbol};
+use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+
+///Definitions and native functions that the free variables of a lambda are evaluated with
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let mut env = Environment::new();
+///env.define("twice", lambda!("%f|x.(f (f x))"));
+///env.register_native("len", 1, |a: &[Lambda]| match &a[0] {
+///    Lambda::Constant(Constant::Str(s)) => Ok(Lambda::Constant(Constant::Int(s.len() as i64))),
+///    l => Err(format!("expected a string, found {}", l)),
+///});
+///println!("{}", env.evaluate(lambda!("((twice (%n.(* n n))) (len \"abc\"))")).unwrap());
+/// //outputs 81
+///println!("{}", env.evaluate(lambda!("len 3")).unwrap_err());
+/// //outputs Native function len failed: expected a string, found 3
+///```
+///
+///Free variables are replaced with the definition or native function they name before evaluating, a definition being marked for alpha reduction.
+///A free variable that names neither panics like Lambda.evaluate().
+///
+///Lambdas are evaluated like Lambda.evaluate(), with delta rules for the built-in operators, see Constant.
+#[derive(Default)]
+pub struct Environment {
+    definitions: Vec<(Symbol, Lambda)>,
+    natives: HashMap<Symbol, Native>,
+}
+
+//Rust function called with the inputs of a native function
+type NativeFn = Box<dyn Fn(&[Lambda]) -> Result<Lambda, String>>;
+
+//native function with the number of inputs it takes
+struct Native {
+    arity: usize,
+    f: NativeFn,
+}
+
+///Error given by a native function while evaluating a lambda
+#[derive(Debug, PartialEq, Clone)]
+pub struct NativeError {
+    ///Name of the native function
+    pub name: String,
+    ///Error message given by the native function
+    pub message: String,
+}
+
+///Step of the evaluation of a lambda in an Environment, see Environment.trace()
+#[derive(Debug, PartialEq, Clone)]
+pub enum TraceStep {
+    ///Beta reduction, with the lambda it gave
+    Beta(Lambda),
+    ///Delta rule of a built-in operator, with the lambda it gave
+    Delta(Lambda),
+    ///Call of a native function, with its evaluated inputs and its output
+    Native {
+        ///Name of the native function
+        name: String,
+        ///Inputs the function was called with
+        inputs: Vec<Lambda>,
+        ///Lambda the function gave
+        output: Lambda,
+    },
+}
+
+//what the head of a lambda being evaluated lets it do next
+enum Head {
+    Beta,
+    Delta(usize),
+    Native(Symbol),
+    Done,
+}
+
+impl Environment {
+    ///Make an empty environment
+    pub fn new() -> Environment {
+        Environment::default()
+    }
+    ///Define a name as a lambda, replacing any definition already using the name
+    ///
+    ///The lambda can use the definitions and native functions already in the environment.
+    pub fn define(&mut self, name: &str, l: Lambda) {
+        let name = Symbol::new(name);
+        let l = self.resolve(&l);
+        self.definitions.retain(|(n, _)| *n != name);
+        self.definitions.push((name, l));
+    }
+    ///Get the lambda a name is defined as
+    pub fn get(&self, name: &str) -> Option<&Lambda> {
+        self.definitions
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, l)| l)
+    }
+    ///Get every definition, in the order they were defined
+    pub fn definitions(&self) -> impl Iterator<Item = (&str, &Lambda)> {
+        self.definitions.iter().map(|(n, l)| (n.as_str(), l))
+    }
+    ///Register a Rust function as a native function, called once it is applied to arity inputs
+    ///
+    ///The inputs are evaluated before the function is called, like the inputs of built-in operators,
+    ///and the error the function gives stops the evaluation as a NativeError.
+    pub fn register_native<F, E>(&mut self, name: &str, arity: usize, f: F)
+    where
+        F: Fn(&[Lambda]) -> Result<Lambda, E> + 'static,
+        E: fmt::Display,
+    {
+        if arity == 0 {
+            panic!("Cannot register native function {} without inputs", name);
+        }
+        let f = Box::new(move |a: &[Lambda]| f(a).map_err(|e| e.to_string()));
+        self.natives.insert(Symbol::new(name), Native { arity, f });
+    }
+    ///Evaluate a lambda with the definitions and native functions of the environment
+    pub fn evaluate(&self, l: Lambda) -> Result<Lambda, NativeError> {
+        let l = self.resolve(&l);
+        l.check_closed();
+        Ok(self.eval(l.alpha_reduce(), &mut None)?.alpha_reduce())
+    }
+    ///Evaluate a lambda like Environment.evaluate(), also giving every step taken
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let mut env = Environment::new();
+    ///env.register_native("double", 1, |a: &[Lambda]| match &a[0] {
+    ///    Lambda::Constant(Constant::Int(n)) => Ok(Lambda::Constant(Constant::Int(n * 2))),
+    ///    _ => Err("expected an integer"),
+    ///});
+    ///let (l, trace) = env.trace(lambda!("(%x.(double x)) (+ 1 2)")).unwrap();
+    ///for step in trace {
+    ///    println!("{}", step);
+    ///}
+    /// //outputs beta (double ((+ 1) 2))
+    /// //outputs delta 3
+    /// //outputs native (double 3) = 6
+    ///println!("{}", l);
+    /// //outputs 6
+    ///```
+    ///
+    ///Inputs evaluated for a built-in operator or native function have their steps before the step using them.
+    pub fn trace(&self, l: Lambda) -> Result<(Lambda, Vec<TraceStep>), NativeError> {
+        let l = self.resolve(&l);
+        l.check_closed();
+        let mut trace = Some(Vec::new());
+        let l = self.eval(l.alpha_reduce(), &mut trace)?;
+        Ok((l.alpha_reduce(), trace.unwrap_or_default()))
+    }
+    //reduce the lambda until it cannot be reduced anymore, calling native functions once they have every input
+    fn eval(
+        &self,
+        mut l: Lambda,
+        trace: &mut Option<Vec<TraceStep>>,
+    ) -> Result<Lambda, NativeError> {
+        loop {
+            match self.head(&l) {
+                Head::Beta => {
+                    l = l.reduce();
+                    if let Some(t) = trace {
+                        t.push(TraceStep::Beta(l.clone()));
+                    }
+                }
+                Head::Delta(evaluated) => {
+                    let (head, mut inputs) = unwind(l);
+                    for a in inputs.iter_mut().take(evaluated) {
+                        *a = self.eval(a.take(), trace)?;
+                    }
+                    l = rewind(head, inputs).delta();
+                    if let Some(t) = trace {
+                        t.push(TraceStep::Delta(l.clone()));
+                    }
+                }
+                Head::Native(name) => {
+                    let native = &self.natives[&name];
+                    let (_, mut inputs) = unwind(l);
+                    let rest = inputs.split_off(native.arity);
+                    for a in inputs.iter_mut() {
+                        *a = self.eval(a.take(), trace)?;
+                    }
+                    let output = (native.f)(&inputs).map_err(|message| NativeError {
+                        name: name.to_string(),
+                        message,
+                    })?;
+                    //the output is marked so its variables stay apart from the rest of the lambda
+                    l = rewind(Lambda::AlphaMark(Box::new(output.clone())), rest).alpha_reduce();
+                    if let Some(t) = trace {
+                        t.push(TraceStep::Native {
+                            name: name.to_string(),
+                            inputs,
+                            output,
+                        });
+                    }
+                }
+                Head::Done => return Ok(l),
+            }
+        }
+    }
+    //find what the lambda reduces with next, from the head of its applications
+    fn head(&self, l: &Lambda) -> Head {
+        let mut inputs = 0;
+        let mut head = l;
+        while let Lambda::Reducible((a, _)) = head {
+            inputs += 1;
+            head = a;
+        }
+        match head {
+            _ if inputs == 0 => Head::Done,
+            Lambda::Func(_) => Head::Beta,
+            Lambda::Constant(Constant::Op(op)) if inputs >= op.arity() => {
+                Head::Delta(op.evaluated())
+            }
+            Lambda::Constant(Constant::Native(name)) => match self.natives.get(name) {
+                Some(n) if inputs >= n.arity => Head::Native(*name),
+                Some(_) => Head::Done,
+                None => panic!("Cannot reduce, unknown native function {}", name),
+            },
+            //an operator applied to fewer inputs than it takes is a value
+            Lambda::Constant(Constant::Op(_)) => Head::Done,
+            _ => panic!("Cannot reduce"),
+        }
+    }
+    //put the definitions and native functions in place of the free variables naming them
+    fn resolve(&self, l: &Lambda) -> Lambda {
+        //steps left to do, visiting a lambda or building one from the finished ones
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Func,
+            Reducible,
+            Mark(Vec<Symbol>),
+        }
+        let mut bound: Vec<Symbol> = Vec::new();
+        let mut stack = vec![Step::Visit(l)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Lambda::Variable(a)) => {
+                    if bound.contains(a) {
+                        out.push(Lambda::Variable(*a));
+                    } else if let Some((_, d)) = self.definitions.iter().find(|(n, _)| n == a) {
+                        out.push(Lambda::AlphaMark(Box::new(d.clone())));
+                    } else if self.natives.contains_key(a) {
+                        out.push(Lambda::Constant(Constant::Native(*a)));
+                    } else {
+                        out.push(Lambda::Variable(*a));
+                    }
+                }
+                Step::Visit(Lambda::Func((a, b))) => {
+                    if let Lambda::Variable(c) = &**a {
+                        bound.push(*c);
+                    }
+                    out.push((**a).clone());
+                    stack.push(Step::Func);
+                    stack.push(Step::Visit(b));
+                }
+                Step::Visit(Lambda::Reducible((a, b))) => {
+                    stack.push(Step::Reducible);
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Visit(a));
+                }
+                //a section marked for alpha reduction cannot see the variables outside of it
+                Step::Visit(Lambda::AlphaMark(a)) => {
+                    stack.push(Step::Mark(mem::take(&mut bound)));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::Constant(c)) => out.push(Lambda::Constant(c.clone())),
+                Step::Visit(l) => panic!("Cannot evaluate {:?}", l),
+                Step::Func => {
+                    bound.pop();
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(Lambda::Func((Box::new(a), Box::new(b))));
+                }
+                Step::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(a.attach(b));
+                }
+                Step::Mark(b) => {
+                    bound = b;
+                    let a = out.pop().unwrap();
+                    out.push(Lambda::AlphaMark(Box::new(a)));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+}
+
+impl Lambda {
+    ///Write the lambda with every part of it that is a definition of the environment, or a definition applied to inputs, as the name of the definition
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let mut env = Environment::new();
+    ///env.define("true", lambda!("%x|y.x"));
+    ///env.define("pair", lambda!("%x|y|f.((f x) y)"));
+    ///let l = env.evaluate(lambda!("(pair true) #3")).unwrap();
+    ///println!("{}", l);
+    /// //outputs (%x.((x (%y|z.y)) (%w|a.(w (w (w a))))))
+    ///println!("{}", l.display_with(&env));
+    /// //outputs ((pair true) #3)
+    ///```
+    ///
+    ///A part is written as a definition if it is alpha equivalent to it, or to the output of the definition with its first inputs replaced by lambdas,
+    ///the earliest definition being used if several fit, and any other part is written like {:#} writes it.
+    ///Outputs giving back one of those inputs as the whole output of a function are not used, as they would fit any function ignoring its input:
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let mut env = Environment::new();
+    ///env.define("true", lambda!("%x|y.x"));
+    ///println!("{}", lambda!("%f|x.x").display_with(&env));
+    /// //outputs #0
+    ///println!("{}", lambda!("%a.((%b.b) (%c.c))").display_with(&env));
+    /// //outputs (%a.((%b.b) (%c.c)))
+    ///```
+    pub fn display_with(&self, env: &Environment) -> String {
+        Lambda::display_sugared(self, &|l| env.fold(l).or_else(|| l.sugar()))
+    }
+}
+
+impl Environment {
+    //find the definition the lambda is, or the definition and the inputs it is applied to
+    fn fold<'a>(&self, l: &'a Lambda) -> Option<Sugar<'a>> {
+        for (name, d) in &self.definitions {
+            if instantiate(unmark(d), &[], l).is_some() {
+                return Some(Sugar::Applied(name.to_string(), Vec::new()));
+            }
+        }
+        for (name, d) in &self.definitions {
+            let mut vars = Vec::new();
+            let mut body = unmark(d);
+            while let Lambda::Func((a, b)) = body
+                && let Lambda::Variable(a) = &**a
+            {
+                vars.push(*a);
+                body = unmark(b);
+                //the output left has to be a function, so a definition is not found in every lambda it could give
+                if !matches!(body, Lambda::Func(_)) {
+                    break;
+                }
+                //an input only given back as the output of a function would fit any function ignoring its input
+                let free = body.free_vars();
+                if vars.iter().any(|v| !free.iter().any(|f| f == v.as_str()))
+                    || (1..vars.len()).any(|i| vars[..i].contains(&vars[i]))
+                    || returns_input(body, &vars)
+                {
+                    continue;
+                }
+                if let Some(inputs) = instantiate(body, &vars, l) {
+                    return Some(Sugar::Applied(name.to_string(), inputs));
+                }
+            }
+        }
+        None
+    }
+}
+
+//find the lambdas to put in place of the variables to make the pattern alpha equivalent to the lambda, if there are any
+fn instantiate<'a>(pattern: &Lambda, vars: &[Symbol], l: &'a Lambda) -> Option<Vec<&'a Lambda>> {
+    //the variables bound around the lambdas being compared, as a chain of pattern and lambda variable pairs
+    let mut bound: Vec<(Symbol, Symbol, usize)> = Vec::new();
+    let mut inputs: Vec<Option<&Lambda>> = vec![None; vars.len()];
+    let mut stack = vec![(pattern, l, usize::MAX)];
+    while let Some((p, l, at)) = stack.pop() {
+        match (unmark(p), unmark(l)) {
+            (Lambda::Variable(a), l) => {
+                //the nearest binding of either variable has to bind both
+                let mut i = at;
+                while i != usize::MAX {
+                    let (c, d, parent) = bound[i];
+                    if c == *a || matches!(l, Lambda::Variable(b) if *b == d) {
+                        break;
+                    }
+                    i = parent;
+                }
+                if i != usize::MAX {
+                    if *a != bound[i].0 || !matches!(l, Lambda::Variable(b) if *b == bound[i].1) {
+                        return None;
+                    }
+                    continue;
+                }
+                match vars.iter().position(|v| v == a) {
+                    Some(v) => {
+                        //an input cannot use the variables bound around it
+                        let free = l.free_vars();
+                        let mut i = at;
+                        while i != usize::MAX {
+                            if free.iter().any(|f| f == bound[i].1.as_str()) {
+                                return None;
+                            }
+                            i = bound[i].2;
+                        }
+                        match inputs[v] {
+                            Some(prev) if instantiate(prev, &[], l).is_none() => return None,
+                            Some(_) => {}
+                            None => inputs[v] = Some(l),
+                        }
+                    }
+                    None if matches!(l, Lambda::Variable(b) if b == a) => {}
+                    None => return None,
+                }
+            }
+            (Lambda::Func((a, b)), Lambda::Func((c, d))) => match (&**a, &**c) {
+                (Lambda::Variable(a), Lambda::Variable(c)) => {
+                    bound.push((*a, *c, at));
+                    stack.push((b, d, bound.len() - 1));
+                }
+                _ => return None,
+            },
+            (Lambda::Reducible((a, b)), Lambda::Reducible((c, d))) => {
+                stack.push((b, d, at));
+                stack.push((a, c, at));
+            }
+            (Lambda::Constant(a), Lambda::Constant(b)) if a == b => {}
+            _ => return None,
+        }
+    }
+    inputs.into_iter().collect()
+}
+
+//check if a function in the lambda outputs one of the variables without using it, like %y.x does
+fn returns_input(l: &Lambda, vars: &[Symbol]) -> bool {
+    let mut stack = vec![l];
+    while let Some(l) = stack.pop() {
+        match unmark(l) {
+            Lambda::Func((a, b)) => match unmark(b) {
+                Lambda::Variable(v) if vars.contains(v) && **a != Lambda::Variable(*v) => {
+                    return true;
+                }
+                b => stack.push(b),
+            },
+            Lambda::Reducible((a, b)) => {
+                stack.push(a);
+                stack.push(b);
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+//the lambda inside any alpha reduction marks around it
+fn unmark(mut l: &Lambda) -> &Lambda {
+    while let Lambda::AlphaMark(a) = l {
+        l = a;
+    }
+    l
+}
+
+//split a lambda into the head of its applications and the inputs applied to it, in order
+fn unwind(mut l: Lambda) -> (Lambda, Vec<Lambda>) {
+    let mut inputs = Vec::new();
+    while let Lambda::Reducible(_) = l {
+        let (f, a) = l.into_pair();
+        inputs.push(a);
+        l = f;
+    }
+    inputs.reverse();
+    (l, inputs)
+}
+
+//apply a lambda to the inputs, in order
+fn rewind(mut l: Lambda, inputs: Vec<Lambda>) -> Lambda {
+    for a in inputs {
+        l = l.attach(a);
+    }
+    l
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Beta(l) => write!(f, "beta {}", l),
+            Self::Delta(l) => write!(f, "delta {}", l),
+            Self::Native {
+                name,
+                inputs,
+                output,
+            } => {
+                write!(f, "native ({}", name)?;
+                for a in inputs {
+                    write!(f, " {}", a)?;
+                }
+                write!(f, ") = {}", output)
+            }
+        }
+    }
+}
+
+impl fmt::Display for NativeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Native function {} failed: {}", self.name, self.message)
+    }
+}
+
+impl std::error::Error for NativeError {}
@@ -0,0 +1,92 @@
+//! Krivine abstract machine for call-by-name evaluation
+
+use crate::debruijn::{Closure, bind, lookup, read_closure};
+use crate::{DeBruijn, Lambda};
+
+///Abstract machine reducing a de Bruijn lambda to weak head normal form, using call-by-name
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let d = DeBruijn::from(&lambda!("((%x|y.x) (%z.z)) (%w.(w w))"));
+///let mut m = KrivineMachine::new(&d);
+///m.run();
+///println!("{} in {} steps", m.read_back(), m.steps());
+/// //outputs (%x.x) in 2 steps
+///```
+///
+///#### Machine method:
+///
+///The machine holds the lambda being reduced, an environment of closures for its variables, and a stack of closures applied to it.
+///A closure is a lambda together with the environment it was made in, so no lambda is ever copied or substituted into.
+///
+///Applying a lambda pushes a closure of the input onto the stack.
+///A function pops a closure off the stack and adds it to the environment, which counts as a single step of beta reduction.
+///A variable is replaced by the closure it has in the environment.
+///
+///The machine stops when it reaches a function with nothing on the stack, the same weak head normal form Lambda.evaluate() stops at.
+pub struct KrivineMachine<'a> {
+    closure: Closure<'a>,
+    stack: Vec<Closure<'a>>,
+    steps: usize,
+    transitions: usize,
+}
+
+impl<'a> KrivineMachine<'a> {
+    ///Make a new machine to reduce a lambda
+    pub fn new(term: &'a DeBruijn) -> KrivineMachine<'a> {
+        KrivineMachine {
+            closure: Closure { term, env: None },
+            stack: Vec::new(),
+            steps: 0,
+            transitions: 0,
+        }
+    }
+    ///Do a single transition of the machine, returning false if the lambda is already in weak head normal form
+    pub fn step(&mut self) -> bool {
+        match self.closure.term {
+            DeBruijn::Reducible((a, b)) => {
+                self.stack.push(Closure {
+                    term: b,
+                    env: self.closure.env.clone(),
+                });
+                self.closure.term = a;
+            }
+            DeBruijn::Func(b) => match self.stack.pop() {
+                Some(c) => {
+                    self.closure.env = bind(self.closure.env.take(), c);
+                    self.closure.term = b;
+                    self.steps += 1;
+                }
+                None => return false,
+            },
+            DeBruijn::Variable(i) => {
+                self.closure = lookup(&self.closure.env, *i);
+            }
+        }
+        self.transitions += 1;
+        true
+    }
+    ///Run the machine until the lambda is in weak head normal form
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+    ///Number of beta reduction steps done so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    ///Number of transitions done so far, including pushing inputs and looking up variables
+    pub fn transitions(&self) -> usize {
+        self.transitions
+    }
+    ///Read back the current state of the machine as a lambda
+    ///
+    ///The environment is substituted into the lambda, and the closures on the stack are applied to it.
+    pub fn read_back(&self) -> Lambda {
+        let mut out = read_closure(&self.closure);
+        for c in self.stack.iter().rev() {
+            out = out.attach(read_closure(c));
+        }
+        out
+    }
+}
@@ -0,0 +1,219 @@
+//! LaTeX and MathJax pretty-printing
+
+use crate::Lambda;
+
+///Options for printing a lambda as LaTeX, see Lambda.to_latex()
+#[derive(Debug, PartialEq, Clone)]
+pub struct LatexOptions {
+    ///Output for MathJax instead of LaTeX, this changes how highlighting and derivations are written
+    pub mathjax: bool,
+    ///Combine nested functions under a single λ, eg: \lambda x\,y.\,x instead of \lambda x.\,\lambda y.\,x
+    pub merge_functions: bool,
+    ///Index of a reducible to highlight, counting only reducibles with a function on the left side, from left to right
+    pub highlight: Option<usize>,
+    ///Highlight the reducible reduced by each step of a derivation, see Lambda.to_latex_derivation()
+    pub highlight_steps: bool,
+    ///Color used for highlighting
+    pub color: String,
+    ///Maximum number of beta reduction steps in a derivation
+    pub max_steps: usize,
+}
+
+impl Default for LatexOptions {
+    fn default() -> LatexOptions {
+        LatexOptions {
+            mathjax: false,
+            merge_functions: true,
+            highlight: None,
+            highlight_steps: true,
+            color: "yellow".to_string(),
+            max_steps: 100,
+        }
+    }
+}
+
+//where a lambda is placed in the lambda containing it, to know when it needs brackets
+#[derive(PartialEq, Clone, Copy)]
+enum Position {
+    Outer,
+    Left,
+    Right,
+}
+
+impl Lambda {
+    ///Print the lambda as LaTeX math, with as few brackets as possible
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("%x.(x y)").to_latex(&LatexOptions::default()));
+    /// //outputs \lambda x.\,x\;y
+    ///let options = LatexOptions {
+    ///    highlight: Some(0),
+    ///    ..Default::default()
+    ///};
+    ///println!("{}", lambda!("(%x.x) ((%y.y) z)").to_latex(&options));
+    /// //outputs \colorbox{yellow}{$(\lambda x.\,x)\;((\lambda y.\,y)\;z)$}
+    ///```
+    ///
+    ///#### Printing method:
+    ///
+    ///Applications are left associative and function outputs extend as far right as possible, brackets are only added where they are needed.
+    ///
+    ///Variables longer than one character are printed with \mathit, and constants with \mathtt.
+    ///
+    ///Sections marked for alpha reduction are printed without a mark, alpha reduce the lambda first if they share variable names.
+    ///
+    ///The highlighted reducible is wrapped in \colorbox, or in \bbox for MathJax.
+    ///Highlighting Some(0) highlights the reducible that Lambda.reduce() reduces next.
+    pub fn to_latex(&self, options: &LatexOptions) -> String {
+        Self::latex(self, options, Position::Outer, &mut 0)
+    }
+    //recursive function to print a lambda, counting the reducibles passed so far to find the highlighted one
+    fn latex(l: &Lambda, options: &LatexOptions, pos: Position, n: &mut usize) -> String {
+        match l {
+            Self::Variable(a) => Self::latex_var(a.as_str()),
+            Self::Func((a, b)) => {
+                let mut s1 = Self::latex_var(&Self::display(a));
+                let mut b = b;
+                while options.merge_functions
+                    && let Self::Func((c, d)) = &**b
+                {
+                    s1.push_str("\\,");
+                    s1.push_str(&Self::latex_var(&Self::display(c)));
+                    b = d;
+                }
+                let out = format!(
+                    "\\lambda {}.\\,{}",
+                    &s1,
+                    Self::latex(b, options, Position::Outer, n)
+                );
+                if pos == Position::Outer {
+                    return out;
+                }
+                format!("({})", &out)
+            }
+            Self::Reducible((a, b)) => {
+                let highlight = if let Self::Func(_) = **a {
+                    *n += 1;
+                    options.highlight == Some(*n - 1)
+                } else {
+                    false
+                };
+                let s1 = Self::latex(a, options, Position::Left, n);
+                let s2 = Self::latex(b, options, Position::Right, n);
+                let mut out = format!("{}\\;{}", &s1, &s2);
+                if pos == Position::Right {
+                    out = format!("({})", &out);
+                }
+                if highlight {
+                    return Self::latex_highlight(&out, options);
+                }
+                out
+            }
+            Self::AlphaMark(a) => Self::latex(a, options, pos, n),
+            Self::Constant(c) => format!("\\mathtt{{{}}}", latex_escape(&c.to_string())),
+            _ => panic!("Cannot print {:?}", l),
+        }
+    }
+    //print a variable name, with \mathit if it is longer than one character
+    fn latex_var(a: &str) -> String {
+        if a.chars().count() > 1 {
+            return format!("\\mathit{{{}}}", a);
+        }
+        a.to_string()
+    }
+    //wrap latex in the highlighting command
+    fn latex_highlight(s: &str, options: &LatexOptions) -> String {
+        if options.mathjax {
+            return format!("\\bbox[{}]{{{}}}", options.color, s);
+        }
+        format!("\\colorbox{{{}}}{{${}$}}", options.color, s)
+    }
+    ///Print the beta reduction steps of evaluating the lambda as an aligned LaTeX derivation
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let options = LatexOptions {
+    ///    highlight_steps: false,
+    ///    ..Default::default()
+    ///};
+    ///println!("{}", lambda!("(%x.(x x)) (%y.y)").to_latex_derivation(&options));
+    /// //outputs \begin{align*}
+    /// //&(\lambda x.\,x\;x)\;(\lambda y.\,y) \\
+    /// //&\to_\beta (\lambda y.\,y)\;(\lambda y.\,y) \\
+    /// //&\to_\beta \lambda y.\,y
+    /// //\end{align*}
+    ///```
+    ///
+    ///#### Derivation method:
+    ///
+    ///The lambda is alpha reduced, then reduced with Lambda.reduce() until it cannot be reduced anymore, like Lambda.evaluate(), with one line per step.
+    ///Steps of beta reduction are written \to_\beta and delta rules of operators \to_\delta, a delta rule evaluating the inputs the operator needs in the same step.
+    ///If options.max_steps steps are reached the derivation ends with \cdots.
+    ///
+    ///The derivation is wrapped in an align* environment, or an aligned environment for MathJax which has to be placed inside math delimiters.
+    pub fn to_latex_derivation(&self, options: &LatexOptions) -> String {
+        let line_options = LatexOptions {
+            highlight: if options.highlight_steps {
+                Some(0)
+            } else {
+                None
+            },
+            ..options.clone()
+        };
+        let mut l = self.clone().alpha_reduce();
+        let mut lines = vec![format!("&{}", l.to_latex(&line_options))];
+        let mut steps = 0;
+        while l.can_reduce() {
+            let arrow = if l.is_saturated() {
+                "\\delta"
+            } else {
+                "\\beta"
+            };
+            if steps == options.max_steps {
+                lines.push(format!("&\\to_{} \\cdots", arrow));
+                break;
+            }
+            l = l.reduce();
+            steps += 1;
+            lines.push(format!("&\\to_{} {}", arrow, l.to_latex(&line_options)));
+        }
+        let env = if options.mathjax { "aligned" } else { "align*" };
+        format!(
+            "\\begin{{{}}}\n{}\n\\end{{{}}}",
+            env,
+            lines.join(" \\\\\n"),
+            env
+        )
+    }
+    //check if Lambda.reduce() can reduce the lambda
+    fn can_reduce(&self) -> bool {
+        if self.is_saturated() {
+            return true;
+        }
+        match self {
+            Self::Reducible((a, _)) => matches!(**a, Self::Func(_)) || a.can_reduce(),
+            _ => false,
+        }
+    }
+}
+
+//escape the characters LaTeX treats specially, for printing constants
+fn latex_escape(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        match c {
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\\' => out.push_str("\\textbackslash{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '^' => out.push_str("\\textasciicircum{}"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,120 @@
+//! Lazy Krivine machine for call-by-need evaluation
+
+use crate::debruijn::{Closure, Frame, bind, frame, read_closure};
+use crate::{DeBruijn, Lambda};
+use std::rc::Rc;
+
+///Abstract machine reducing a de Bruijn lambda to weak head normal form, using call-by-need
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let d = DeBruijn::from(&lambda!("(%x.(x x)) ((%y.y) (%z.z))"));
+///let mut m = LazyMachine::new(&d);
+///m.run();
+///println!("{} in {} steps", m.read_back(), m.steps());
+/// //outputs (%x.x) in 3 steps
+///let mut k = KrivineMachine::new(&d);
+///k.run();
+///println!("{} in {} steps", k.read_back(), k.steps());
+/// //outputs (%x.x) in 4 steps
+///```
+///
+///#### Machine method:
+///
+///The machine works like a KrivineMachine, holding the lambda being reduced, an environment of closures for its variables, and a stack of closures applied to it.
+///
+///Every input added to the environment is a shared thunk: every copy of the environment points to the same one.
+///The first time a variable is used, an update marker is pushed onto the stack before its closure is reduced.
+///When a function reaches the update marker, the thunk is replaced by the function, so every other use of the variable gets the function without reducing the input again.
+///
+///This makes steps() count every input reduction once, however many times the variable is used, unlike call-by-name.
+pub struct LazyMachine<'a> {
+    closure: Closure<'a>,
+    stack: Vec<Entry<'a>>,
+    steps: usize,
+    updates: usize,
+    transitions: usize,
+}
+
+//an entry of the stack, an input applied to the lambda or a thunk to update with its value
+enum Entry<'a> {
+    Input(Closure<'a>),
+    Update(Rc<Frame<'a>>),
+}
+
+impl<'a> LazyMachine<'a> {
+    ///Make a new machine to reduce a lambda
+    pub fn new(term: &'a DeBruijn) -> LazyMachine<'a> {
+        LazyMachine {
+            closure: Closure { term, env: None },
+            stack: Vec::new(),
+            steps: 0,
+            updates: 0,
+            transitions: 0,
+        }
+    }
+    ///Do a single transition of the machine, returning false if the lambda is already in weak head normal form
+    pub fn step(&mut self) -> bool {
+        match self.closure.term {
+            DeBruijn::Reducible((a, b)) => {
+                self.stack.push(Entry::Input(Closure {
+                    term: b,
+                    env: self.closure.env.clone(),
+                }));
+                self.closure.term = a;
+            }
+            DeBruijn::Func(b) => match self.stack.pop() {
+                Some(Entry::Update(f)) => {
+                    *f.closure.borrow_mut() = self.closure.clone();
+                    self.updates += 1;
+                }
+                Some(Entry::Input(c)) => {
+                    self.closure.env = bind(self.closure.env.take(), c);
+                    self.closure.term = b;
+                    self.steps += 1;
+                }
+                None => return false,
+            },
+            DeBruijn::Variable(i) => {
+                let f = frame(&self.closure.env, *i).clone();
+                let c = f.closure.borrow().clone();
+                //functions are already values, so only other lambdas need updating
+                if !matches!(c.term, DeBruijn::Func(_)) {
+                    self.stack.push(Entry::Update(f));
+                }
+                self.closure = c;
+            }
+        }
+        self.transitions += 1;
+        true
+    }
+    ///Run the machine until the lambda is in weak head normal form
+    pub fn run(&mut self) {
+        while self.step() {}
+    }
+    ///Number of beta reduction steps done so far
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    ///Number of thunks replaced by their value so far
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+    ///Number of transitions done so far, including pushing inputs, looking up variables and updating thunks
+    pub fn transitions(&self) -> usize {
+        self.transitions
+    }
+    ///Read back the current state of the machine as a lambda
+    ///
+    ///The environment is substituted into the lambda, with every thunk that has been updated replaced by its value, and the inputs on the stack are applied to it.
+    pub fn read_back(&self) -> Lambda {
+        let mut out = read_closure(&self.closure);
+        for e in self.stack.iter().rev() {
+            if let Entry::Input(c) = e {
+                out = out.attach(read_closure(c));
+            }
+        }
+        out
+    }
+}
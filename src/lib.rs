@@ -4,25 +4,89 @@
 //! use easy_lambda_calculus::*;
 //!
 //! //code to evaluate and(true, true)
-//!fn main() {
-//!   let t = lambda!("%x|y.x"); //true
-//!   let f = lambda!("%x|y.y"); //false
-//!   let a = lambda!("%x|y.(x y) &{}", f); //and
-//!   let res = lambda!("({} &{}) &{}", a, t.clone(), t); //and(true, true)
-//!   println!("{}", res.evaluate());
-//!}
+//! let t = lambda!("%x|y.x"); //true
+//! let f = lambda!("%x|y.y"); //false
+//! let a = lambda!("%x|y.(x y) &{}", f); //and
+//! let res = lambda!("({} &{}) &{}", a, t.clone(), t); //and(true, true)
+//! println!("{}", res.evaluate());
 //! //outputs (%x|y.x) which is equivalent to true
 
 use std::collections::HashMap;
 use std::fmt;
 use std::mem;
 
+pub mod arena;
+pub mod bytecode;
+mod cek;
+mod codegen;
+mod debruijn;
+mod dot;
+mod encoding;
+mod environment;
+mod krivine;
+mod latex;
+mod lazy;
+mod nbe;
+mod optimal;
+mod primitive;
+#[cfg(feature = "serde")]
+pub mod serialize;
+mod sugar;
+mod symbol;
+pub mod system_f;
+
+use bytecode::{Program, Vm};
+pub use cek::{CekMachine, Continuation};
+pub use debruijn::DeBruijn;
+///Derive FromLambda for structs and enums, decoding the lambdas the IntoLambda derive encodes them as, with the derive feature
+#[cfg(feature = "derive")]
+pub use easy_lambda_calculus_derive::FromLambda;
+///Derive IntoLambda for structs and enums, with the derive feature, used together with the FromLambda derive above
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///#[derive(IntoLambda, FromLambda, Debug, PartialEq)]
+///#[lambda(church)]
+///enum Tree {
+///    Leaf,
+///    Node(Box<Tree>, u64, Box<Tree>),
+///}
+///
+///let t = Tree::Node(Box::new(Tree::Leaf), 2, Box::new(Tree::Leaf));
+///let sum = lambda!("%t.((t &(%f|x.x)) &(%l|n|r|f|x.((l f) ((n f) ((r f) x)))))");
+///let n: u64 = lambda!("{} {}", sum, t.into_lambda()).evaluate().decode().unwrap();
+///println!("{}", n);
+/// //outputs 2
+///let t: Tree = Tree::Node(Box::new(Tree::Leaf), 2, Box::new(Tree::Leaf)).into_lambda().evaluate().decode().unwrap();
+///println!("{:?}", t);
+/// //outputs Node(Leaf, 2, Leaf)
+///```
+///
+///Structs have one constructor and enums one for every variant, each encoded with Lambda::constructor().
+///Data is Scott encoded, or Church encoded with `#[lambda(church)]`, folding every field of type Self or `Box<Self>`.
+///
+///Deriving IntoLambda also adds lambda_constructors() and lambda_destructor() to the type, the lambdas building and matching on it.
+#[cfg(feature = "derive")]
+pub use easy_lambda_calculus_derive::IntoLambda;
+pub use encoding::{FromLambda, FromLambdaError, IntoLambda, ListEncoding, Numeral};
+pub use environment::{Environment, NativeError, TraceStep};
+pub use krivine::KrivineMachine;
+pub use latex::LatexOptions;
+pub use lazy::LazyMachine;
+pub use nbe::Value;
+pub use optimal::InteractionNet;
+pub use primitive::{Constant, Operator};
+pub use sugar::ParseOptions;
+use sugar::Sugar;
+pub use symbol::Symbol;
+
 ///Makes a new lambda from a string
 ///
 ///```rust
 ///use easy_lambda_calculus::*;
 ///
-///let l = lambda!("%x|y.y")
+///let l = lambda!("%x|y.y");
 ///println!("{}", lambda!("%x|y.(x y) &{}", l));
 /// //outputs λx|y.(x y) &(λx|y.y)
 ///```
@@ -42,6 +106,23 @@ use std::mem;
 ///&(x) is used to mark section x for alpha reduction, so you can reuse variable names without any unintended interactions.
 ///
 ///{} is used to input a lambda variable into the lambda, uses the same syntax as the `format!()` macro, &{} is shorthand for &({}).
+///
+///letrec f = e in b to define f as e inside both e and b, so f can call itself, with b going on until the bracket around the letrec closes.
+///It is the Z combinator applied to %f.e, so recursive functions work with every evaluation strategy.
+///
+///data List = Nil | Cons h t; to declare a data type, its constructors being usable anywhere after the declaration, and (Cons 1 Nil) applies a constructor to every input after it.
+///match l { Nil -> a, Cons h t -> b } to take the case of the constructor l was built with, binding its fields, with _ -> c for every constructor without a case.
+///Constructors are Scott encoded, so a match is l applied to one function for each constructor, in the order they were declared.
+///
+///<a, b, c> to make a tuple, nested Church pairs ending with %x|y.y, and t.1 to take its item at index 1.
+///{name = e, age = f} to make a record, a tuple of the values sorted by field name, and r.name to take a field, found at its place in the records written in the same lambda.
+///
+///#5 to make the Church numeral 5, and [a, b, c] to make the Church list of a, b and c, see Lambda::parse_with() to use other encodings.
+///
+///42, #t, "hi" and the operators +, -, *, == and if are constants, see Constant, so these cannot be used as variable names.
+///match, letrec and in are keywords and cannot be used as variable names either, while data is only a keyword at the start of the lambda or after the ; of a declaration.
+///
+///lambda!() panics on invalid syntax, use Lambda::try_new() to get a ParseError instead.
 #[macro_export]
 macro_rules! lambda {
     ($x:expr) => (
@@ -54,16 +135,42 @@ macro_rules! lambda {
 }
 
 ///Lambda data type
-#[derive(Debug, PartialEq, Clone)]
+///
+///Lambdas are built, cloned, compared, printed, reduced and dropped using work stacks instead of recursion,
+///so lambdas with millions of nested nodes do not overflow the stack.
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let l = lambda!("%x|y.{}", Lambda::from_i32(1000000));
+///println!("{}", l.to_string().len());
+/// //outputs 4000012
+///```
+///
+///Printing a lambda with {:#} writes every part of it shaped like a tuple, a Church numeral or a Church or Scott list with the syntax for it.
+///(%x|y.y) is written #0, being both the Church numeral 0 and the empty Church list.
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let l = lambda!("<1, <#t, 3>>");
+///println!("{}", l);
+/// //outputs (%z.((z 1) (%z.((z (%x.((x #t) (%x.((x 3) &(%x|y.y)))))) &(%x|y.y)))))
+///println!("{:#}", l);
+/// //outputs <1, <#t, 3>>
+///```
+#[derive(Debug)]
 pub enum Lambda {
     ///Function
     Func((Box<Lambda>, Box<Lambda>)),
-    ///Variable
-    Variable(String),
+    ///Variable, with its interned name
+    Variable(Symbol),
     ///Marks a lambda being applied into a function
     Reducible((Box<Lambda>, Box<Lambda>)),
     ///Marks a lambda for alpha reduction
     AlphaMark(Box<Lambda>),
+    ///Primitive constant or built-in operator, see Constant
+    Constant(Constant),
 
     ///Vector of strings to be converted into a lambda
     StVec(Vec<String>),
@@ -83,6 +190,59 @@ impl Lambda {
     //new lambda from formatted string
     #[doc(hidden)]
     pub fn new(s: &str, f: Vec<Lambda>) -> Lambda {
+        Self::parse_with(s, f, &ParseOptions::default())
+    }
+    ///Make a new lambda from a string like lambda!(), giving an error instead of panicking if it is not valid syntax
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Lambda::try_new("(%x.x) {}", vec![lambda!("y")]).unwrap());
+    /// //outputs ((%x.x) y)
+    ///println!("{}", Lambda::try_new("(%x.x", vec![]).unwrap_err());
+    /// //outputs Unclosed bracket
+    ///```
+    pub fn try_new(s: &str, f: Vec<Lambda>) -> Result<Lambda, ParseError> {
+        Self::try_parse_with(s, f, &ParseOptions::default())
+    }
+    ///Make a new lambda from a string like lambda!(), with the inputs for every {} and options for the encoding of literals
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///let options = ParseOptions {
+    ///    list: ListEncoding::Scott,
+    ///    ..Default::default()
+    ///};
+    ///println!("{}", Lambda::parse_with("[#1]", vec![], &options));
+    /// //outputs (%x|y.((y &(%f|x.(f x))) &(%n|c.n)))
+    ///```
+    ///
+    ///Panics if the string is not valid syntax, see Lambda::try_parse_with().
+    pub fn parse_with(s: &str, f: Vec<Lambda>, options: &ParseOptions) -> Lambda {
+        match Self::try_parse_with(s, f, options) {
+            Ok(l) => l,
+            Err(e) => panic!("{}", e),
+        }
+    }
+    ///Make a new lambda like Lambda::parse_with(), giving an error instead of panicking if it is not valid syntax
+    pub fn try_parse_with(
+        s: &str,
+        mut f: Vec<Lambda>,
+        options: &ParseOptions,
+    ) -> Result<Lambda, ParseError> {
+        //names the sugar binds must not capture the free variables of the inputs
+        let free: Vec<String> = f.iter().flat_map(|l| l.free_vars()).collect();
+        let s = Self::extract_strings(s, &mut f)?;
+        let s = Self::desugar_numerals(&s, &mut f, options)?;
+        let s = Self::desugar_data(&s, &free)?;
+        let s = Self::desugar_tuples(&s, &free)?;
+        let s = Self::desugar_letrec(&s)?;
+        let s = Self::desugar_lists(&s, &mut f, options)?;
+        Self::parse_core(&s, f)
+    }
+    //parse lambda syntax without sugar, putting the inputs in place of every {} in order
+    fn parse_core(s: &str, mut f: Vec<Lambda>) -> Result<Lambda, ParseError> {
         let chars = s
             .chars()
             .collect::<Vec<char>>()
@@ -102,12 +262,12 @@ impl Lambda {
             .iter()
             .map(|x| x.to_string())
             .collect::<Vec<String>>();
-        let bracks = Self::find_bracks(chars, false);
-        let tokens = Self::parse_bracks(bracks, &f, 0).0;
+        let bracks = Self::find_bracks(chars, false)?;
+        let tokens = Self::parse_bracks(bracks, &mut f, 0)?.0;
         Self::parse_tokens(tokens)
     }
     //order characters by brackets
-    fn find_bracks(chars: Vec<String>, alph: bool) -> Lambda {
+    fn find_bracks(chars: Vec<String>, alph: bool) -> Result<Lambda, ParseError> {
         //find brackets
         let mut starts: Vec<usize> = Vec::new();
         let mut ends: Vec<usize> = Vec::new();
@@ -119,7 +279,7 @@ impl Lambda {
                     ends.push(i);
                     count -= 1;
                 }
-                (")", 0) => panic!("Unclosed bracket"),
+                (")", 0) => return Err(ParseError::Syntax("Unclosed bracket".to_string())),
                 (")", _) => count -= 1,
                 ("(", 0) => {
                     if i != 0 && chars[i - 1] == "&" {
@@ -133,13 +293,15 @@ impl Lambda {
             }
         }
         if starts.len() != ends.len() {
-            panic!("Unclosed bracket");
+            return Err(ParseError::Syntax("Unclosed bracket".to_string()));
         }
         //split the string into bracket tokens and mark them for alpha reduction if needed
         if starts.is_empty() && alph {
-            return Self::AlphaMark(Box::new(Self::Brack(vec![Self::StVec(chars)])));
+            return Ok(Self::AlphaMark(Box::new(Self::Brack(vec![Self::StVec(
+                chars,
+            )]))));
         } else if starts.is_empty() {
-            return Self::Brack(vec![Self::StVec(chars)]);
+            return Ok(Self::Brack(vec![Self::StVec(chars)]));
         }
         let mut bracks: Vec<Lambda> = Vec::new();
         if !chars[..starts[0]].to_vec().is_empty() {
@@ -150,12 +312,12 @@ impl Lambda {
                 bracks.push(Self::find_bracks(
                     chars[starts[i] + 1..ends[i]].to_vec(),
                     true,
-                ));
+                )?);
             } else {
                 bracks.push(Self::find_bracks(
                     chars[starts[i] + 1..ends[i]].to_vec(),
                     false,
-                ));
+                )?);
             }
             if i + 1 != starts.len() {
                 bracks.push(Self::StVec(chars[ends[i] + 1..starts[i + 1]].to_vec()));
@@ -164,115 +326,116 @@ impl Lambda {
             }
         }
         if alph {
-            return Self::AlphaMark(Box::new(Self::Brack(bracks)));
+            return Ok(Self::AlphaMark(Box::new(Self::Brack(bracks))));
         }
-        Self::Brack(bracks)
+        Ok(Self::Brack(bracks))
     }
     //parse the brackets and characters into brackets and tokens
-    fn parse_bracks(brs: Lambda, vec: &Vec<Lambda>, mut vec_num: usize) -> (Lambda, usize) {
-        if let Self::Brack(br) = brs {
+    fn parse_bracks(
+        mut brs: Lambda,
+        vec: &mut [Lambda],
+        mut vec_num: usize,
+    ) -> Result<(Lambda, usize), ParseError> {
+        if let Self::Brack(br) = &mut brs {
             let mut parse_vec: Vec<Lambda> = Vec::new();
-            for b in br {
+            for b in mem::take(br) {
                 match &b {
                     Self::Brack(_) => {
-                        let t: Lambda;
-                        (t, vec_num) = Self::parse_bracks(b, vec, vec_num);
-                        if let Self::Brack(v) = &t
+                        let mut t: Lambda;
+                        (t, vec_num) = Self::parse_bracks(b, vec, vec_num)?;
+                        if let Self::Brack(v) = &mut t
                             && v.len() == 1
                         {
-                            parse_vec.push(v[0].clone());
+                            parse_vec.push(v[0].take());
                         } else {
                             parse_vec.push(t);
                         }
                     }
                     Self::StVec(v) => {
                         let t: Vec<Lambda>;
-                        (t, vec_num) = Self::parse_stvec(v.clone(), vec, vec_num);
-                        parse_vec.extend_from_slice(&t[..]);
+                        (t, vec_num) = Self::parse_stvec(v.clone(), vec, vec_num)?;
+                        parse_vec.extend(t);
                     }
                     Self::AlphaMark(l) => {
                         let temp: Lambda;
-                        (temp, vec_num) = Self::parse_bracks(*l.clone(), vec, vec_num);
+                        (temp, vec_num) = Self::parse_bracks(*l.clone(), vec, vec_num)?;
                         parse_vec.push(Self::AlphaMark(Box::new(temp)));
                     }
-                    _ => panic!("Syntax error"),
+                    _ => return Err(ParseError::Syntax("Syntax error".to_string())),
                 }
             }
-            return (Self::Brack(parse_vec), vec_num);
+            return Ok((Self::Brack(parse_vec), vec_num));
         }
-        panic!("Syntax error")
+        Err(ParseError::Syntax("Syntax error".to_string()))
     }
     //turn the characters into tokens
-    fn parse_stvec(strs: Vec<String>, vec: &[Lambda], mut vec_num: usize) -> (Vec<Lambda>, usize) {
+    fn parse_stvec(
+        strs: Vec<String>,
+        vec: &mut [Lambda],
+        mut vec_num: usize,
+    ) -> Result<(Vec<Lambda>, usize), ParseError> {
         let mut token_vec: Vec<Lambda> = Vec::new();
         let mut pass_num = 0;
         for i in 0..strs.len() {
             if pass_num > 0 {
                 pass_num -= 1;
             } else if strs[i] == "%" {
-                (token_vec, pass_num) = Self::parse_func_char(strs.clone(), token_vec, i);
-            } else if strs[i] == "{" && strs[i + 1] == "}" {
-                token_vec.push(Self::Container(Box::new(vec[vec_num].clone())));
-                vec_num += 1;
-                pass_num += 1;
-            } else {
-                for j in i..strs.len() {
-                    match strs[j].as_str() {
-                        " " => {
-                            token_vec.push(Self::AttPl(()));
-                        }
-                        "&" => {}
-                        "{" => {}
-                        "}" => {}
-                        _ => {
-                            (token_vec, pass_num) = Self::find_vars(&strs, token_vec, j);
-                        }
+                (token_vec, pass_num) = Self::parse_func_char(strs.clone(), token_vec, i)?;
+            } else if strs[i] == "{" && strs.get(i + 1).is_some_and(|s| s == "}") {
+                match vec.get_mut(vec_num) {
+                    Some(l) => token_vec.push(Self::Container(Box::new(l.take()))),
+                    None => {
+                        return Err(ParseError::Syntax(
+                            "Syntax error, more {} than inputs".to_string(),
+                        ));
                     }
                 }
+                vec_num += 1;
                 pass_num += 1;
+            } else if strs[i] == " " {
+                token_vec.push(Self::AttPl(()));
+            } else if !matches!(strs[i].as_str(), "&" | "{" | "}") {
+                let token: Lambda;
+                (token, pass_num) = Self::find_token(&strs[i..])?;
+                token_vec.push(token);
             }
         }
-        (token_vec, vec_num)
+        Ok((token_vec, vec_num))
     }
-    //find variable tokens
-    fn find_vars(strs: &[String], mut token_vec: Vec<Lambda>, i: usize) -> (Vec<Lambda>, i32) {
-        let mut pass_num = 0;
-        if !Self::ALPH.contains(&strs[i]) {
-            panic!("Syntax error {}", &strs[i]);
-        }
-        let mut var: String = "".to_string();
-        for k in i..strs.len() {
-            if !Self::ALPH.contains(&strs[k]) {
-                token_vec.push(Self::Variable(var));
-                pass_num += 1;
-                break;
-            }
-            var.push_str(&strs[k]);
-            if k == strs.len() - 1 {
-                token_vec.push(Self::Variable(var));
-                pass_num += 1;
-                break;
-            }
-            pass_num += 1;
+    //find a variable or constant token, and how many characters after its first one to pass
+    fn find_token(strs: &[String]) -> Result<(Lambda, i32), ParseError> {
+        let len = strs
+            .iter()
+            .position(|c| matches!(c.as_str(), " " | "&" | "{" | "}"))
+            .unwrap_or(strs.len());
+        let token = strs[..len].concat();
+        if let Some(c) = Constant::parse(&token) {
+            Ok((Self::Constant(c), len as i32 - 1))
+        } else if token.chars().all(|c| Self::ALPH.contains(c)) {
+            Ok((Self::var(token.as_str()), len as i32 - 1))
+        } else {
+            Err(ParseError::Syntax(format!("Syntax error {}", token)))
         }
-        (token_vec, pass_num)
     }
     //parse the function syntax
     fn parse_func_char(
         strs: Vec<String>,
         mut token_vec: Vec<Lambda>,
         i: usize,
-    ) -> (Vec<Lambda>, i32) {
-        let mut pass_num: i32 = 0;
+    ) -> Result<(Vec<Lambda>, i32), ParseError> {
         let mut val_vec: Vec<String> = Vec::new();
         let mut var: String = "".to_string();
-        for st in strs[i + 1..].iter() {
+        for (pass_num, st) in (0_i32..).zip(strs[i + 1..].iter()) {
             match st.as_str() {
+                "." | "|" if var.is_empty() => {
+                    return Err(ParseError::Syntax(
+                        "Syntax error, function input without a name".to_string(),
+                    ));
+                }
                 "." => {
                     val_vec.push(var);
                     token_vec.push(Self::TFunc(val_vec));
-                    pass_num += 1;
-                    break;
+                    return Ok((token_vec, pass_num + 1));
                 }
                 "|" => {
                     val_vec.push(var);
@@ -282,77 +445,115 @@ impl Lambda {
                     if Self::ALPH.contains(st) {
                         var.push_str(st);
                     } else {
-                        panic!("Syntax error");
+                        return Err(ParseError::Syntax("Syntax error".to_string()));
                     }
                 }
             }
-            pass_num += 1;
         }
-        (token_vec, pass_num)
+        Err(ParseError::Syntax(
+            "Syntax error, expected . after the inputs of a function".to_string(),
+        ))
     }
     //turn brackets and tokens into a lambda
-    fn parse_tokens(token: Lambda) -> Lambda {
-        match token {
-            Self::Brack(v) => Self::parse_token_vec(v),
-            Self::Variable(_) => token,
-            Self::AlphaMark(l) => Self::AlphaMark(Box::new(Self::parse_tokens(*l))),
-            Self::Reducible((a, b)) => Self::parse_tokens(*a).attach(Self::parse_tokens(*b)),
-            Self::Func((a, b)) => Self::Func((a, Box::new(Self::parse_tokens(*b)))),
-            Self::Container(l) => *l,
-            _ => panic!("syntax error"),
+    fn parse_tokens(mut token: Lambda) -> Result<Lambda, ParseError> {
+        match &mut token {
+            Self::Brack(v) => Self::parse_token_vec(mem::take(v)),
+            //functions and reducibles are only made from lambdas that are already parsed
+            Self::Variable(_) | Self::Constant(_) | Self::Reducible(_) | Self::Func(_) => Ok(token),
+            Self::AlphaMark(l) => Ok(Self::AlphaMark(Box::new(Self::parse_tokens(l.take())?))),
+            Self::Container(l) => Ok(l.take()),
+            _ => Err(ParseError::Syntax("syntax error".to_string())),
         }
     }
     //turn a vec of tokens into a lambda
-    fn parse_token_vec(mut vec: Vec<Lambda>) -> Lambda {
-        let temp_vec = vec.clone();
-        for (i, l) in temp_vec.iter().enumerate() {
-            if let Self::AttPl(_) = *l {
-                vec = [
-                    &vec[..i - 1],
-                    &vec![
-                        Self::parse_tokens(vec[i - 1].clone())
-                            .attach(Self::parse_tokens(vec[i + 1].clone())),
-                    ][..],
-                    &vec[i + 2..],
-                ]
-                .concat();
-            }
+    fn parse_token_vec(mut vec: Vec<Lambda>) -> Result<Lambda, ParseError> {
+        if vec.is_empty() {
+            return Err(ParseError::Syntax(
+                "Syntax error, empty bracket".to_string(),
+            ));
+        }
+        if matches!(vec[0], Self::AttPl(_)) || matches!(vec[vec.len() - 1], Self::AttPl(_)) {
+            return Err(ParseError::Syntax(
+                "Syntax error, space at the start or end of a bracket".to_string(),
+            ));
         }
-        match vec[0].clone() {
-            Self::TFunc(v) => Self::parse_func_token(v, vec),
-            Self::Brack(_) => Self::parse_tokens(vec[0].clone()),
-            Self::Reducible(_) => Self::parse_tokens(vec[0].clone()),
-            Self::Variable(_) => vec[0].clone(),
-            Self::Container(_) => vec[0].clone(),
-            Self::AlphaMark(a) => Self::AlphaMark(Box::new(Self::parse_tokens(*a))),
-            _ => panic!("Syntax error"),
+        let places: Vec<usize> = (0..vec.len())
+            .filter(|i| matches!(vec[*i], Self::AttPl(_)))
+            .collect();
+        //an operator is applied to every input after it, each reducible made shortening the vec by two
+        let prefix = matches!(vec[0], Self::Constant(Constant::Op(_)));
+        if !prefix && places.len() > 1 {
+            return Err(ParseError::Syntax(
+                "Syntax error, apply one input per bracket, eg: ((x y) z)".to_string(),
+            ));
+        }
+        for (n, i) in places.into_iter().enumerate() {
+            let i = if prefix { i - 2 * n } else { i };
+            let b = Self::parse_tokens(vec[i + 1].take())?;
+            let a = Self::parse_tokens(vec[i - 1].take())?;
+            vec.splice(i - 1..i + 2, [a.attach(b)]);
+        }
+        match &mut vec[0] {
+            Self::TFunc(v) => {
+                let v = mem::take(v);
+                Self::parse_func_token(v, vec)
+            }
+            Self::Brack(_) => Self::parse_tokens(vec[0].take()),
+            Self::Reducible(_) => Self::parse_tokens(vec[0].take()),
+            Self::Variable(_) | Self::Constant(_) => Ok(vec[0].take()),
+            Self::Container(_) => Self::parse_tokens(vec[0].take()),
+            Self::AlphaMark(a) => Ok(Self::AlphaMark(Box::new(Self::parse_tokens(a.take())?))),
+            _ => Err(ParseError::Syntax("Syntax error".to_string())),
         }
     }
     //turn the shorthand function token into lambda functions
-    fn parse_func_token(vec: Vec<String>, tokens: Vec<Lambda>) -> Lambda {
+    fn parse_func_token(vec: Vec<String>, mut tokens: Vec<Lambda>) -> Result<Lambda, ParseError> {
         if !vec.is_empty() {
-            return Self::func(
+            return Ok(Self::func(
                 vec[0].as_str(),
-                Self::parse_func_token(vec[1..vec.len()].to_vec(), tokens),
-            );
+                Self::parse_func_token(vec[1..vec.len()].to_vec(), tokens)?,
+            ));
         }
         if tokens.is_empty() {
-            panic!("Syntax error");
+            return Err(ParseError::Syntax("Syntax error".to_string()));
         }
-        Self::parse_token_vec(tokens[1..tokens.len()].to_vec())
+        Self::parse_token_vec(tokens.split_off(1))
     }
-    //make new function variant with a string and a Lambda
-    fn func(a: &str, b: Lambda) -> Lambda {
+    //make new function variant with a name and a Lambda
+    fn func(a: impl Into<Symbol>, b: Lambda) -> Lambda {
         Self::Func((Box::new(Self::var(a)), Box::new(b)))
     }
-    //make new variable variant with a string
-    fn var(inp: &str) -> Lambda {
-        Self::Variable(inp.to_string())
+    //make new variable variant with a name
+    fn var(inp: impl Into<Symbol>) -> Lambda {
+        Self::Variable(inp.into())
     }
     //make new reductible variant by attaching an input Lambda to a Lambda
     fn attach(self, a: Lambda) -> Lambda {
         Self::Reducible((Box::new(self), Box::new(a)))
     }
+    //take the lambda out, leaving a token in its place, as lambdas cannot be moved out of a variant
+    fn take(&mut self) -> Lambda {
+        mem::replace(self, Self::AttPl(()))
+    }
+    //move every lambda inside this one onto a stack, leaving tokens in their place
+    fn take_inside(&mut self, stack: &mut Vec<Lambda>) {
+        match self {
+            Self::Func((a, b)) | Self::Reducible((a, b)) => {
+                stack.push(a.take());
+                stack.push(b.take());
+            }
+            Self::AlphaMark(a) | Self::Container(a) => stack.push(a.take()),
+            Self::Brack(v) => stack.append(v),
+            _ => {}
+        }
+    }
+    //take the two lambdas out of a function or reducible
+    fn into_pair(mut self) -> (Lambda, Lambda) {
+        match &mut self {
+            Self::Func((a, b)) | Self::Reducible((a, b)) => (a.take(), b.take()),
+            _ => panic!("Cannot split {:?}", self),
+        }
+    }
 
     ///A single step of beta reduction
     ///
@@ -372,17 +573,36 @@ impl Lambda {
     ///eg: (((λx.x) (λy.(y y))) (λz.z)) will reduce to ((λy.(y y)) (λz.z))
     ///
     ///For reduction with functions marked for alpha reduction, see Lambda.alpha_reduce().
-    pub fn reduce(self) -> Lambda {
-        if let Self::Reducible((a, b)) = self.clone() {
-            //if reducible has a function reduce the function, else if it has a reducible, reduce the inside reducible
-            if let Self::Func((c, d)) = &*a {
-                return Self::recursive_reduce(*d.clone(), *c.clone(), *b);
-            } else if let Self::Reducible(_) = &*a {
-                return a.reduce().attach(*b);
+    pub fn reduce(mut self) -> Lambda {
+        //find how many reducibles are on the left side before the reducible with a function
+        let mut depth = 0;
+        let mut l = &self;
+        loop {
+            match l {
+                Self::Reducible((a, _)) => match **a {
+                    Self::Func(_) => break,
+                    Self::Reducible(_) => {
+                        depth += 1;
+                        l = a;
+                    }
+                    //an operator is at the head of the whole lambda, so the lambda is reduced with its delta rule
+                    Self::Constant(Constant::Op(_)) => return self.delta(),
+                    _ => panic!("Cannot reduce"),
+                },
+                _ => panic!("Cannot reduce"),
+            }
+        }
+        //substitute the input into the function in place
+        let mut l = &mut self;
+        for _ in 0..depth {
+            if let Self::Reducible((a, _)) = l {
+                l = a;
             }
-            panic!("Cannot reduce");
         }
-        panic!("Cannot reduce");
+        let (f, b) = l.take().into_pair();
+        let (c, d) = f.into_pair();
+        *l = Self::recursive_reduce(d, c, b);
+        self
     }
 
     ///Alpha reduce any sections marked for alpha reduction
@@ -407,33 +627,36 @@ impl Lambda {
     ///Note that reducing them into other functions does not remove that they are marked for alpha reduction, and can cause unwanted effects.
     ///For example if multiple variables are substituted with the section marked for alpha reduction, when alpha reduced, every copy will have different variable names.
     pub fn alpha_reduce(self) -> Lambda {
-        let (m, _, _) = Self::set_map(self.clone(), vec![HashMap::new()], 0, 0, 0);
-        let (out, _) = Self::recursive_alpha(self, m, 0, 0);
-        out
+        let m = Self::set_map(&self);
+        Self::recursive_alpha(&self, &m)
     }
-    //recursive function to substitute every instance of the given variable
-    fn recursive_reduce(b: Lambda, a: Lambda, sub: Lambda) -> Lambda {
-        match b {
-            //if it is a function variant, check for the variable being substituted or reduce an inside function
-            Self::Func((c, d)) => {
-                if a != *d {
-                    return Self::Func((c, Box::new(Self::recursive_reduce(*d, a, sub))));
+    //function to substitute every instance of the given variable in place, using a stack of the lambdas left to visit
+    fn recursive_reduce(mut b: Lambda, a: Lambda, sub: Lambda) -> Lambda {
+        let mut stack: Vec<&mut Lambda> = vec![&mut b];
+        while let Some(l) = stack.pop() {
+            match l {
+                //if it is a function variant, substitute inside the function, unless the function shadows the variable
+                Self::Func((c, d)) => {
+                    if **c != a {
+                        stack.push(d);
+                    }
                 }
-                Self::Func((c, Box::new(sub)))
-            }
-            //if it is a reducible, reduce both the function and the input expression
-            Self::Reducible((c, d)) => Self::recursive_reduce(*c, a.clone(), sub.clone())
-                .attach(Self::recursive_reduce(*d, a, sub)),
-            //if it is just a variable, substitute if it is the variable being substituted
-            Self::Variable(_) => {
-                if a == b {
-                    return sub;
-                }
-                b
+                //if it is a reducible, substitute in both the function and the input expression
+                Self::Reducible((c, d)) => {
+                    stack.push(d);
+                    stack.push(c);
+                }
+                //if it is just a variable, substitute if it is the variable being substituted
+                Self::Variable(_) => {
+                    if *l == a {
+                        *l = sub.clone();
+                    }
+                }
+                Self::AlphaMark(_) | Self::Constant(_) => {}
+                _ => panic!("Cannot reduce {:?}", l),
             }
-            Self::AlphaMark(_) => b,
-            _ => panic!("Cannot reduce {:?}", b),
         }
+        b
     }
     ///Evaluate a lambda
     ///
@@ -450,147 +673,647 @@ impl Lambda {
     ///Evaluation will first alpha reduce the lambda.
     ///It will then automatically beta reduce the lambda until it cannot be reduced anymore.
     ///Lastly it will alpha reduce the lambda again, to output it with predictable names.
+    ///
+    ///The lambda must be closed, evaluating a lambda with free variables panics, see Lambda.free_vars() and Lambda.try_evaluate().
     pub fn evaluate(self) -> Lambda {
+        self.check_closed();
         self.alpha_reduce().recursive_evaluate().alpha_reduce()
     }
-    //function to recursively reduce every reducible
-    fn recursive_evaluate(self) -> Lambda {
-        if let Self::Reducible(_) = self {
-            return self.reduce().recursive_evaluate();
+    ///Evaluate a lambda like Lambda.evaluate(), giving an error instead of panicking if it cannot be evaluated
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("(%x.x) y").try_evaluate().unwrap_err());
+    /// //outputs Cannot evaluate, free variables: y
+    ///```
+    pub fn try_evaluate(self) -> Result<Lambda, EvalError> {
+        self.try_evaluate_with(Strategy::Substitution)
+    }
+    ///Evaluate a lambda like Lambda.evaluate_with(), giving an error instead of panicking if it cannot be evaluated with the strategy
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("(+ 1 2)").try_evaluate_with(Strategy::Krivine).unwrap_err());
+    /// //outputs Cannot evaluate constants with Krivine, only Substitution applies delta rules
+    ///```
+    pub fn try_evaluate_with(self, strategy: Strategy) -> Result<Lambda, EvalError> {
+        self.evaluable(strategy)?;
+        Ok(self.evaluate_with(strategy))
+    }
+    ///Evaluate a lambda with the chosen evaluation strategy
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("(%x.&(%x.&(%x.x))) &(%x.x)").evaluate_with(Strategy::Krivine));
+    /// //outputs (%x|y.y)
+    ///```
+    ///
+    ///The lambda must be closed, and is output alpha reduced like Lambda.evaluate(), see Strategy.
+    ///
+    ///Strategies other than Strategy::Substitution never substitute into the lambda, so they cannot capture variables even without sections marked for alpha reduction.
+    ///They do not apply delta rules either, evaluating a lambda with constants with them panics, see Constant.
+    pub fn evaluate_with(self, strategy: Strategy) -> Lambda {
+        if strategy != Strategy::Substitution
+            && let Err(e) = self.evaluable(strategy)
+        {
+            panic!("{}", e);
+        }
+        match strategy {
+            Strategy::Substitution => self.evaluate(),
+            Strategy::Krivine => {
+                let d = DeBruijn::from(&self);
+                let mut m = KrivineMachine::new(&d);
+                m.run();
+                m.read_back().alpha_reduce()
+            }
+            Strategy::CallByNeed => {
+                let d = DeBruijn::from(&self);
+                let mut m = LazyMachine::new(&d);
+                m.run();
+                m.read_back().alpha_reduce()
+            }
+            Strategy::CallByValue => {
+                let d = DeBruijn::from(&self);
+                let mut m = CekMachine::new(&d);
+                m.run();
+                m.read_back().alpha_reduce()
+            }
+            Strategy::Optimal => {
+                let d = DeBruijn::from(&self);
+                let mut net = InteractionNet::new(&d);
+                net.reduce();
+                net.read_back().alpha_reduce()
+            }
+            Strategy::Nbe => {
+                let d = DeBruijn::from(&self);
+                Value::eval(&d).reify().alpha_reduce()
+            }
+            Strategy::Bytecode => {
+                let p = Program::compile(&self);
+                let mut vm = Vm::new(&p);
+                vm.run();
+                vm.read_back().alpha_reduce()
+            }
+        }
+    }
+    //panic if the lambda has free variables, as it cannot be evaluated
+    fn check_closed(&self) {
+        if let Err(e) = self.closed() {
+            panic!("{}", e);
         }
-        self
     }
-    //function to assign a vector of hashmaps to a lambda
-    fn set_map(
-        l: Lambda,
-        mut m: Vec<HashMap<String, usize>>,
-        mut i: usize,
-        al: usize,
-        mut al_in: usize,
-    ) -> (Vec<HashMap<String, usize>>, usize, usize) {
-        match l {
-            Self::Variable(a) => {
-                if !m[al].contains_key(a.as_str()) {
-                    m[al].insert(a, i);
-                    (m, i + 1, al_in)
-                } else {
-                    (m, i, al_in)
+    //check that the lambda has no free variables, as it cannot be evaluated otherwise
+    fn closed(&self) -> Result<(), EvalError> {
+        let free = self.free_vars();
+        if !free.is_empty() {
+            return Err(EvalError::FreeVariables(free));
+        }
+        Ok(())
+    }
+    //check that the strategy can evaluate the lambda, only Strategy::Substitution applies delta rules to constants
+    fn evaluable(&self, strategy: Strategy) -> Result<(), EvalError> {
+        self.closed()?;
+        if strategy != Strategy::Substitution && self.has_constants() {
+            return Err(EvalError::Constants(strategy));
+        }
+        Ok(())
+    }
+    //check if the lambda holds any constant, with a stack of lambdas left to visit
+    fn has_constants(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(l) = stack.pop() {
+            match l {
+                Self::Constant(_) => return true,
+                Self::Func((a, b)) | Self::Reducible((a, b)) => {
+                    stack.push(b);
+                    stack.push(a);
                 }
+                Self::AlphaMark(a) => stack.push(a),
+                _ => {}
+            }
+        }
+        false
+    }
+    //function to reduce every reducible
+    fn recursive_evaluate(mut self) -> Lambda {
+        while let Self::Reducible(_) = self {
+            if self.is_unsaturated() {
+                break;
             }
-            Self::Func((a, b)) => {
-                if let Self::Variable(c) = *a
-                    && !m[al].contains_key(c.as_str())
-                {
-                    m[al].insert(c, i);
+            self = self.reduce();
+        }
+        self
+    }
+    //function to assign a vector of hashmaps to a lambda, numbering variables in the order they show up
+    fn set_map(l: &Lambda) -> Vec<HashMap<Symbol, usize>> {
+        let mut m = vec![HashMap::new()];
+        let mut i = 0;
+        //stack of the lambdas left to visit, with the index of the hashmap they use
+        let mut stack = vec![(l, 0)];
+        while let Some((l, al)) = stack.pop() {
+            match l {
+                Self::Variable(a) => {
+                    if !m[al].contains_key(a) {
+                        m[al].insert(*a, i);
+                        i += 1;
+                    }
+                }
+                Self::Func((a, b)) => {
+                    if let Self::Variable(c) = &**a
+                        && !m[al].contains_key(c)
+                    {
+                        m[al].insert(*c, i);
+                    }
+                    i += 1;
+                    stack.push((b, al));
+                }
+                Self::Reducible((a, b)) => {
+                    stack.push((b, al));
+                    stack.push((a, al));
                 }
-                Self::set_map(*b, m, i + 1, al, al_in)
+                Self::AlphaMark(a) => {
+                    m.push(HashMap::new());
+                    stack.push((a, m.len() - 1));
+                }
+                Self::Constant(_) => {}
+                _ => panic!("Cannot map lambda"),
             }
-            Self::Reducible((a, b)) => {
-                (m, i, al_in) = Self::set_map(*a, m, i, al, al_in);
-                Self::set_map(*b, m, i, al, al_in)
+        }
+        m
+    }
+    ///Get every variable that is not bound by a function, in the order they first show up
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{:?}", lambda!("%x.((x y) &(x))").free_vars());
+    /// //outputs ["y", "x"]
+    ///```
+    ///
+    ///Sections marked for alpha reduction have their own variables, so a variable inside one is only bound by functions inside the same section.
+    pub fn free_vars(&self) -> Vec<String> {
+        //steps left to do, visiting a lambda or removing a function variable when leaving the function
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Unbind,
+            Restore(Vec<Symbol>),
+        }
+        let mut out: Vec<Symbol> = Vec::new();
+        let mut bound: Vec<Symbol> = Vec::new();
+        let mut stack = vec![Step::Visit(self)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Self::Variable(a)) => {
+                    if !bound.contains(a) && !out.contains(a) {
+                        out.push(*a);
+                    }
+                }
+                Step::Visit(Self::Func((a, b))) => {
+                    if let Self::Variable(c) = &**a {
+                        bound.push(*c);
+                        stack.push(Step::Unbind);
+                        stack.push(Step::Visit(b));
+                    }
+                }
+                Step::Visit(Self::Reducible((a, b))) => {
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Visit(a));
+                }
+                //a section marked for alpha reduction cannot see the variables outside of it
+                Step::Visit(Self::AlphaMark(a)) => {
+                    stack.push(Step::Restore(mem::take(&mut bound)));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Self::Constant(_)) => {}
+                Step::Visit(_) => panic!("Cannot find variables"),
+                Step::Unbind => {
+                    bound.pop();
+                }
+                Step::Restore(b) => bound = b,
             }
-            Self::AlphaMark(a) => {
-                al_in += 1;
-                m.push(HashMap::new());
-                Self::set_map(*a, m, i, al_in, al_in)
+        }
+        out.iter().map(|a| a.to_string()).collect()
+    }
+    ///Get every variable bound by a function, in the order the functions show up
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{:?}", lambda!("%x|y.((x z) &(%x.x))").bound_vars());
+    /// //outputs ["x", "y"]
+    ///```
+    pub fn bound_vars(&self) -> Vec<String> {
+        let mut out: Vec<Symbol> = Vec::new();
+        let mut stack = vec![self];
+        while let Some(l) = stack.pop() {
+            match l {
+                Self::Variable(_) | Self::Constant(_) => {}
+                Self::Func((a, b)) => {
+                    if let Self::Variable(c) = &**a
+                        && !out.contains(c)
+                    {
+                        out.push(*c);
+                    }
+                    stack.push(b);
+                }
+                Self::Reducible((a, b)) => {
+                    stack.push(b);
+                    stack.push(a);
+                }
+                Self::AlphaMark(a) => stack.push(a),
+                _ => panic!("Cannot find variables"),
             }
-            _ => panic!("Cannot map lambda"),
         }
+        out.iter().map(|a| a.to_string()).collect()
     }
-    //function to get a lambda variable name from an integer
-    fn get_name(mut n: usize) -> String {
-        let mut out = "".to_string();
-        let chars = Self::ALPH.chars();
-        let num = chars.clone().count();
-        n += 1;
-        while n != 0 {
-            let mut temp = chars.clone().nth((n - 1) % num).unwrap().to_string();
-            temp.push_str(&out);
-            out = temp;
-            n = ((n - 1) - ((n - 1) % num)) / num;
+    ///Check if the lambda has no free variables
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///assert!(lambda!("%x|y.(x y)").is_closed());
+    ///assert!(!lambda!("%x.(x y)").is_closed());
+    ///```
+    pub fn is_closed(&self) -> bool {
+        self.free_vars().is_empty()
+    }
+    ///Count how many times a variable is used, not counting the functions binding it
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", lambda!("%x|y.((x y) x)").occurrences("x"));
+    /// //outputs 2
+    ///```
+    pub fn occurrences(&self, name: &str) -> usize {
+        let name = Symbol::new(name);
+        let mut out = 0;
+        let mut stack = vec![self];
+        while let Some(l) = stack.pop() {
+            match l {
+                Self::Variable(a) => out += (*a == name) as usize,
+                Self::Constant(_) => {}
+                Self::Func((_, b)) => stack.push(b),
+                Self::Reducible((a, b)) => {
+                    stack.push(b);
+                    stack.push(a);
+                }
+                Self::AlphaMark(a) => stack.push(a),
+                _ => panic!("Cannot find variables"),
+            }
         }
         out
     }
-    //recursive function to remove any colliding variable names
-    fn recursive_alpha(
-        l: Lambda,
-        m: Vec<HashMap<String, usize>>,
-        al: usize,
-        mut al_in: usize,
-    ) -> (Lambda, usize) {
-        match l {
-            Self::Variable(a) => match m[al].get(&a) {
-                Some(b) => (Self::Variable(Self::get_name(*b)), al_in),
-                _ => panic!("Cannot alpha reduce"),
-            },
-            Self::Func((a, b)) => {
-                let c: Lambda;
-                let d: Lambda;
-                (c, al_in) = Self::recursive_alpha(*a, m.clone(), al, al_in);
-                (d, al_in) = Self::recursive_alpha(*b, m, al, al_in);
-                (Self::Func((Box::new(c), Box::new(d))), al_in)
-            }
-            Self::Reducible((a, b)) => {
-                let c: Lambda;
-                let d: Lambda;
-                (c, al_in) = Self::recursive_alpha(*a, m.clone(), al, al_in);
-                (d, al_in) = Self::recursive_alpha(*b, m, al, al_in);
-                (c.attach(d), al_in)
+    //function to get a lambda variable name from an integer
+    fn get_name(n: usize) -> Symbol {
+        Symbol::numbered(n, Self::ALPH)
+    }
+    //function to remove any colliding variable names, rebuilding the lambda from a stack of finished lambdas
+    fn recursive_alpha(l: &Lambda, m: &[HashMap<Symbol, usize>]) -> Lambda {
+        //steps left to do, visiting a lambda with the index of its hashmap or building a lambda from the finished ones
+        enum Step<'a> {
+            Visit(&'a Lambda, usize),
+            Func,
+            Reducible,
+        }
+        //find the new name of every variable once, rather than for every time it is used
+        let m: Vec<HashMap<Symbol, Symbol>> = m
+            .iter()
+            .map(|h| h.iter().map(|(a, b)| (*a, Self::get_name(*b))).collect())
+            .collect();
+        let mut al_in = 0;
+        let mut stack = vec![Step::Visit(l, 0)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Self::Variable(a), al) => match m[al].get(a) {
+                    Some(b) => out.push(Self::Variable(*b)),
+                    _ => panic!("Cannot alpha reduce, unmapped variable {}", a),
+                },
+                Step::Visit(Self::Func((a, b)), al) => {
+                    stack.push(Step::Func);
+                    stack.push(Step::Visit(b, al));
+                    stack.push(Step::Visit(a, al));
+                }
+                Step::Visit(Self::Reducible((a, b)), al) => {
+                    stack.push(Step::Reducible);
+                    stack.push(Step::Visit(b, al));
+                    stack.push(Step::Visit(a, al));
+                }
+                Step::Visit(Self::AlphaMark(a), _) => {
+                    al_in += 1;
+                    stack.push(Step::Visit(a, al_in));
+                }
+                Step::Visit(Self::Constant(c), _) => out.push(Self::Constant(c.clone())),
+                Step::Visit(_, _) => panic!("Cannot alpha reduce"),
+                Step::Func => {
+                    let d = out.pop().unwrap();
+                    let c = out.pop().unwrap();
+                    out.push(Self::Func((Box::new(c), Box::new(d))));
+                }
+                Step::Reducible => {
+                    let d = out.pop().unwrap();
+                    let c = out.pop().unwrap();
+                    out.push(c.attach(d));
+                }
             }
-            Self::AlphaMark(a) => {
-                al_in += 1;
-                Self::recursive_alpha(*a, m, al_in, al_in)
+        }
+        out.pop().unwrap()
+    }
+    //function to calculate a string to represent the lambda, using a stack of lambdas and text left to write
+    fn display(l: &Lambda) -> String {
+        Self::display_sugared(l, &|_| None)
+    }
+    //function to calculate a string to represent the lambda, writing the sugared form of any lambda the function gives one for
+    fn display_sugared<'a>(
+        l: &'a Lambda,
+        sugar: &dyn Fn(&'a Lambda) -> Option<Sugar<'a>>,
+    ) -> String {
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Text(&'static str),
+        }
+        let mut out = String::new();
+        let mut stack = vec![Step::Visit(l)];
+        while let Some(step) = stack.pop() {
+            if let Step::Visit(l) = step
+                && let Some(sugared) = sugar(l)
+            {
+                let (items, open, close) = match sugared {
+                    Sugar::Numeral(n) => {
+                        out.push_str(&format!("#{}", n));
+                        continue;
+                    }
+                    Sugar::Applied(name, inputs) => {
+                        out.push_str(&"(".repeat(inputs.len()));
+                        out.push_str(&name);
+                        for a in inputs.into_iter().rev() {
+                            stack.push(Step::Text(")"));
+                            stack.push(Step::Visit(a));
+                            stack.push(Step::Text(" "));
+                        }
+                        continue;
+                    }
+                    Sugar::Tuple(items) => (items, '<', ">"),
+                    Sugar::List(items) => (items, '[', "]"),
+                };
+                out.push(open);
+                stack.push(Step::Text(close));
+                for (i, item) in items.into_iter().enumerate().rev() {
+                    stack.push(Step::Visit(item));
+                    if i > 0 {
+                        stack.push(Step::Text(", "));
+                    }
+                }
+                continue;
             }
-            _ => panic!("Cannot alpha reduce"),
-        }
-    }
-    //function to calculate a string to represent the lambda
-    fn display(l: Lambda) -> String {
-        match l {
-            Self::Variable(a) => a,
-            Self::Func((a, mut b)) => {
-                let d = b.clone();
-                let mut s1 = Self::display(*a);
-                let mut s2 = "".to_string();
-                while let Self::Func((ref a, ref c)) = *b {
-                    s1.push('|');
-                    s1.push_str(&Self::display(*a.clone()));
-                    if let Self::Func(_) = **c {
-                    } else {
-                        s2 = Self::display(*c.clone());
+            match step {
+                Step::Text(t) => out.push_str(t),
+                Step::Visit(Self::Variable(a)) => out.push_str(a.as_str()),
+                Step::Visit(Self::Constant(c)) => out.push_str(&c.to_string()),
+                Step::Visit(Self::Func((a, b))) => {
+                    out.push_str("(%");
+                    out.push_str(&Self::display(a));
+                    let mut b = b;
+                    //a function with a sugared form is written as it, not as more inputs
+                    while let Self::Func((a, c)) = &**b
+                        && sugar(b).is_none()
+                    {
+                        out.push('|');
+                        out.push_str(&Self::display(a));
+                        b = c;
                     }
-                    b = c.clone();
+                    out.push('.');
+                    stack.push(Step::Text(")"));
+                    stack.push(Step::Visit(b));
                 }
-                if s2.is_empty() {
-                    s2 = Self::display(*d)
+                Step::Visit(Self::Reducible((a, b))) => {
+                    out.push('(');
+                    stack.push(Step::Text(")"));
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Text(" "));
+                    stack.push(Step::Visit(a));
                 }
-                format!("(%{}.{})", &s1, &s2)
-            }
-            Self::Reducible((a, b)) => {
-                let s1 = Self::display(*a);
-                let s2 = Self::display(*b);
-                format!("({} {})", &s1, &s2)
-            }
-            Self::AlphaMark(a) => {
-                let s1 = Self::display(*a);
-                format!("&{}", &s1)
+                Step::Visit(Self::AlphaMark(a)) => {
+                    out.push('&');
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(_) => panic!("Cannot display"),
             }
-            _ => panic!("Cannot display"),
         }
+        out
     }
+    ///Make the Church numeral of a number, same as #n in lambda!()
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", Lambda::from_i32(2));
+    /// //outputs (%f|x.(f (f x)))
+    ///```
+    ///
+    ///Panics if the number is negative, as Church numerals are natural numbers.
     pub fn from_i32(n: i32) -> Lambda {
-        let mut l = Self::var("x").attach(Self::var("y"));
-        for i in 0..n {
-            l = Self::var("x").attach(l);
-            println!("{}", i);
+        match u64::try_from(n) {
+            Ok(n) => Numeral::Church.encode(n),
+            Err(_) => panic!("Cannot make a Church numeral from {}", n),
+        }
+    }
+}
+
+///Evaluation strategy used by Lambda.evaluate_with()
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Strategy {
+    ///Reduce the lambda with Lambda.reduce() until it cannot be reduced anymore, same as Lambda.evaluate()
+    Substitution,
+    ///Run the lambda on a KrivineMachine, using closures instead of substitution
+    Krivine,
+    ///Run the lambda on a LazyMachine, sharing the reduction of every input between all of its uses
+    CallByNeed,
+    ///Run the lambda on a CekMachine, evaluating inputs before applying them
+    ///
+    ///This can give a different lambda than the other strategies, as inputs are evaluated before they are placed in the output,
+    ///and a lambda with an input that never reaches a value never stops, even if the input is not used.
+    CallByValue,
+    ///Reduce the lambda in an InteractionNet, sharing every reducible between all of its copies
+    ///
+    ///This reduces inside functions too, so it gives the normal form of the lambda rather than the weak head normal form the other strategies stop at.
+    Optimal,
+    ///Evaluate the lambda into a Value of Rust closures and read back its normal form, see Value
+    ///
+    ///Like Strategy::Optimal this gives the normal form of the lambda, inputs are only evaluated when they are used so it agrees with normal order reduction.
+    Nbe,
+    ///Compile the lambda to a bytecode Program and run it on a Vm, evaluating inputs before applying them like Strategy::CallByValue
+    Bytecode,
+}
+
+///Error given when a lambda cannot be evaluated, see Lambda.try_evaluate()
+#[derive(Debug, PartialEq, Clone)]
+pub enum EvalError {
+    ///The lambda has free variables, with their names
+    FreeVariables(Vec<String>),
+    ///The lambda has constants, which the strategy cannot evaluate
+    Constants(Strategy),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::FreeVariables(v) => {
+                write!(f, "Cannot evaluate, free variables: {}", v.join(", "))
+            }
+            Self::Constants(s) => write!(
+                f,
+                "Cannot evaluate constants with {:?}, only Substitution applies delta rules",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+///Error given when a string is not valid lambda syntax, see Lambda::try_new()
+#[derive(Debug, PartialEq, Clone)]
+pub enum ParseError {
+    ///The string is not valid syntax, with the reason
+    Syntax(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(reason) => write!(f, "{}", reason),
         }
-        l
     }
 }
 
+impl std::error::Error for ParseError {}
+
 //implement display for the lambda data type
 impl fmt::Display for Lambda {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", Lambda::display(self.clone()))
+        if f.alternate() {
+            write!(f, "{}", Lambda::display_sugared(self, &Lambda::sugar))
+        } else {
+            write!(f, "{}", Lambda::display(self))
+        }
+    }
+}
+
+//implement clone with a stack of lambdas left to clone and a stack of finished clones, so deep lambdas do not overflow the stack
+impl Clone for Lambda {
+    fn clone(&self) -> Lambda {
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Build(&'a Lambda),
+        }
+        let mut stack = vec![Step::Visit(self)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(l) => match l {
+                    Lambda::Func((a, b)) | Lambda::Reducible((a, b)) => {
+                        stack.push(Step::Build(l));
+                        stack.push(Step::Visit(b));
+                        stack.push(Step::Visit(a));
+                    }
+                    Lambda::AlphaMark(a) | Lambda::Container(a) => {
+                        stack.push(Step::Build(l));
+                        stack.push(Step::Visit(a));
+                    }
+                    Lambda::Brack(v) => {
+                        stack.push(Step::Build(l));
+                        stack.extend(v.iter().rev().map(Step::Visit));
+                    }
+                    Lambda::Variable(a) => out.push(Lambda::Variable(*a)),
+                    Lambda::Constant(c) => out.push(Lambda::Constant(c.clone())),
+                    Lambda::StVec(v) => out.push(Lambda::StVec(v.clone())),
+                    Lambda::TFunc(v) => out.push(Lambda::TFunc(v.clone())),
+                    Lambda::AttPl(_) => out.push(Lambda::AttPl(())),
+                },
+                Step::Build(l) => match l {
+                    Lambda::Func(_) | Lambda::Reducible(_) => {
+                        let b = Box::new(out.pop().unwrap());
+                        let a = Box::new(out.pop().unwrap());
+                        if let Lambda::Func(_) = l {
+                            out.push(Lambda::Func((a, b)));
+                        } else {
+                            out.push(Lambda::Reducible((a, b)));
+                        }
+                    }
+                    Lambda::AlphaMark(_) => {
+                        let a = Box::new(out.pop().unwrap());
+                        out.push(Lambda::AlphaMark(a));
+                    }
+                    Lambda::Container(_) => {
+                        let a = Box::new(out.pop().unwrap());
+                        out.push(Lambda::Container(a));
+                    }
+                    Lambda::Brack(v) => {
+                        let v = out.split_off(out.len() - v.len());
+                        out.push(Lambda::Brack(v));
+                    }
+                    _ => unreachable!(),
+                },
+            }
+        }
+        out.pop().unwrap()
+    }
+}
+
+//implement equality with a stack of pairs of lambdas left to compare
+impl PartialEq for Lambda {
+    fn eq(&self, other: &Lambda) -> bool {
+        let mut stack = vec![(self, other)];
+        while let Some(pair) = stack.pop() {
+            match pair {
+                (Lambda::Func((a, b)), Lambda::Func((c, d)))
+                | (Lambda::Reducible((a, b)), Lambda::Reducible((c, d))) => {
+                    stack.push((b, d));
+                    stack.push((a, c));
+                }
+                (Lambda::AlphaMark(a), Lambda::AlphaMark(b))
+                | (Lambda::Container(a), Lambda::Container(b)) => stack.push((a, b)),
+                (Lambda::Brack(a), Lambda::Brack(b)) => {
+                    if a.len() != b.len() {
+                        return false;
+                    }
+                    stack.extend(a.iter().zip(b.iter()).rev());
+                }
+                (Lambda::Variable(a), Lambda::Variable(b)) => {
+                    if a != b {
+                        return false;
+                    }
+                }
+                (Lambda::Constant(a), Lambda::Constant(b)) => {
+                    if a != b {
+                        return false;
+                    }
+                }
+                (Lambda::StVec(a), Lambda::StVec(b)) | (Lambda::TFunc(a), Lambda::TFunc(b)) => {
+                    if a != b {
+                        return false;
+                    }
+                }
+                (Lambda::AttPl(_), Lambda::AttPl(_)) => {}
+                _ => return false,
+            }
+        }
+        true
+    }
+}
+
+//implement drop by moving the inside lambdas onto a stack, so dropping deep lambdas does not overflow the stack
+impl Drop for Lambda {
+    fn drop(&mut self) {
+        let mut stack: Vec<Lambda> = Vec::new();
+        self.take_inside(&mut stack);
+        while let Some(mut l) = stack.pop() {
+            l.take_inside(&mut stack);
+        }
     }
 }
@@ -1,12 +1,60 @@
 use easy_lambda_calculus::*;
+use std::env;
+use std::process;
 
-//code to evaluate and(true, true)
+const USAGE: &str = "usage: easy_lambda_calculus <command> <lambda>
+
+commands:
+    eval <lambda>                evaluate the lambda
+    dot <lambda>                 print the syntax tree in Graphviz DOT format
+    graph <lambda> [max_nodes]   print the beta reduction graph in Graphviz DOT format, max_nodes defaults to 100
+    codegen --target <target> <lambda>
+                                 print a standalone program running the lambda, target is rust, js or c";
+
+//command line interface, eg: easy_lambda_calculus graph "(%x.(x x)) (%y.y)" | dot -Tsvg > graph.svg
 fn main() {
-    /*let t = lambda!("%x|y.x"); //true
-    let f = lambda!("%x|y.y"); //false
-    let a = lambda!("%x|y.(x y) &{}", f); //and
-    let res = lambda!("({} &{}) &{}", a, t.clone(), t); //and(true, true)
-    println!("{}", res.evaluate());*/
-    Lambda::from_i32(100000);
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+    if args[0] == "codegen" {
+        codegen(&args);
+        return;
+    }
+    let l = lambda!(args[1].as_str());
+    match (args[0].as_str(), args.len()) {
+        ("eval", 2) => println!("{}", l.evaluate()),
+        ("dot", 2) => println!("{}", l.to_dot()),
+        ("graph", 2) => println!("{}", l.reduction_graph_dot(100)),
+        ("graph", 3) => match args[2].parse() {
+            Ok(n) => println!("{}", l.reduction_graph_dot(n)),
+            Err(_) => {
+                eprintln!("max_nodes must be a number\n\n{}", USAGE);
+                process::exit(1);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(1);
+        }
+    }
+}
+
+//print a program generated from the lambda, eg: easy_lambda_calculus codegen --target c "%x|y.x" > true.c
+fn codegen(args: &[String]) {
+    if args.len() != 4 || args[1] != "--target" {
+        eprintln!("{}", USAGE);
+        process::exit(1);
+    }
+    let l = lambda!(args[3].as_str());
+    match args[2].as_str() {
+        "rust" => print!("{}", l.emit_rust()),
+        "js" => print!("{}", l.emit_js()),
+        "c" => print!("{}", l.emit_c()),
+        _ => {
+            eprintln!("target must be rust, js or c\n\n{}", USAGE);
+            process::exit(1);
+        }
+    }
 }
-//outputs (%x|y.x) which is equivalent to true
@@ -0,0 +1,170 @@
+//! Normalization by evaluation, turning lambdas into Rust closures
+
+use crate::{DeBruijn, Lambda};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+///Meaning of a de Bruijn lambda, used to find its normal form by normalization by evaluation
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let d = DeBruijn::from(&lambda!("(%f.(%x.(f (f x)))) (%f.(%x.(f (f x))))"));
+///println!("{}", Value::eval(&d).reify());
+/// //outputs (%x|y.(x (x (x (x y)))))
+///let not = DeBruijn::from(&lambda!("%b.((b &(%x|y.y)) &(%x|y.x))"));
+///let t = DeBruijn::from(&lambda!("%x|y.x"));
+///println!("{}", Value::eval(&not).apply(&Value::eval(&t)).reify());
+/// //outputs (%x|y.y)
+///```
+///
+///#### Evaluation method:
+///
+///Every function is turned into a Rust closure, which evaluates the output of the function when it is called with an input.
+///Inputs are shared thunks, only evaluated the first time they are used, so a lambda whose normal form exists always reaches it.
+///
+///Applying a variable that is not bound to an input gives a neutral value, remembering the variable and the inputs applied to it.
+///Reifying a value calls every closure with a new neutral variable and reads back the result, naming the variables with the same naming scheme as Lambda.alpha_reduce().
+///
+///This reduces inside functions too, so reify() gives the normal form of the lambda like Strategy::Optimal.
+///Evaluation calls the closures on the Rust stack, so unlike the abstract machines it can overflow the stack on lambdas that nest reductions very deeply.
+#[derive(Clone)]
+pub struct Value<'a>(Rc<Shape<'a>>);
+
+//a value is a function, or a variable with the inputs applied to it
+enum Shape<'a> {
+    Func(Box<dyn Fn(Thunk<'a>) -> Value<'a> + 'a>),
+    Neutral(Neutral<'a>),
+}
+
+//a value that cannot be reduced further, as it is stuck on a variable with no input, numbered by how many functions it is inside of
+enum Neutral<'a> {
+    Variable(usize),
+    Apply((Value<'a>, Thunk<'a>)),
+}
+
+//an input, evaluated the first time it is needed and then shared by every use
+type Thunk<'a> = Rc<RefCell<Delay<'a>>>;
+
+enum Delay<'a> {
+    Delayed((&'a DeBruijn, Env<'a>)),
+    Forced(Value<'a>),
+    Forcing,
+}
+
+//linked list of inputs, the first being for variable 0
+type Env<'a> = Option<Rc<Frame<'a>>>;
+
+struct Frame<'a> {
+    thunk: Thunk<'a>,
+    next: Env<'a>,
+}
+
+impl<'a> Value<'a> {
+    ///Evaluate a lambda into a value
+    pub fn eval(term: &'a DeBruijn) -> Value<'a> {
+        eval(term, None)
+    }
+    ///Apply an input into the value
+    pub fn apply(&self, input: &Value<'a>) -> Value<'a> {
+        self.apply_thunk(Rc::new(RefCell::new(Delay::Forced(input.clone()))))
+    }
+    ///Read back the normal form of the value as a lambda
+    pub fn reify(&self) -> Lambda {
+        //steps left to do, visiting a value or an input with the number of functions entered, or building from the finished lambdas
+        enum Step<'a> {
+            Visit(Value<'a>, usize),
+            Force(Thunk<'a>, usize),
+            Func(usize),
+            Reducible,
+        }
+        let mut stack = vec![Step::Visit(self.clone(), 0)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(v, depth) => match &*v.0 {
+                    Shape::Func(f) => {
+                        let x = Value(Rc::new(Shape::Neutral(Neutral::Variable(depth))));
+                        stack.push(Step::Func(depth));
+                        stack.push(Step::Visit(
+                            f(Rc::new(RefCell::new(Delay::Forced(x)))),
+                            depth + 1,
+                        ));
+                    }
+                    Shape::Neutral(Neutral::Variable(n)) => {
+                        out.push(Lambda::Variable(Lambda::get_name(*n)))
+                    }
+                    Shape::Neutral(Neutral::Apply((a, t))) => {
+                        stack.push(Step::Reducible);
+                        stack.push(Step::Force(t.clone(), depth));
+                        stack.push(Step::Visit(a.clone(), depth));
+                    }
+                },
+                Step::Force(t, depth) => stack.push(Step::Visit(force(&t), depth)),
+                Step::Func(n) => {
+                    let b = out.pop().unwrap();
+                    out.push(Lambda::func(Lambda::get_name(n), b));
+                }
+                Step::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(a.attach(b));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+    //apply an input into the value, calling it if it is a function
+    fn apply_thunk(&self, t: Thunk<'a>) -> Value<'a> {
+        match &*self.0 {
+            Shape::Func(f) => f(t),
+            Shape::Neutral(_) => Value(Rc::new(Shape::Neutral(Neutral::Apply((self.clone(), t))))),
+        }
+    }
+}
+
+//evaluate a lambda with an environment giving its variables
+fn eval<'a>(term: &'a DeBruijn, env: Env<'a>) -> Value<'a> {
+    match term {
+        DeBruijn::Variable(i) => force(&lookup(&env, *i)),
+        DeBruijn::Func(b) => Value(Rc::new(Shape::Func(Box::new(move |t| {
+            eval(
+                b,
+                Some(Rc::new(Frame {
+                    thunk: t,
+                    next: env.clone(),
+                })),
+            )
+        })))),
+        DeBruijn::Reducible((a, b)) => {
+            let f = eval(a, env.clone());
+            //a variable input shares the thunk it is bound to, rather than delaying a lookup of it
+            let t = match &**b {
+                DeBruijn::Variable(i) => lookup(&env, *i),
+                _ => Rc::new(RefCell::new(Delay::Delayed((b, env)))),
+            };
+            f.apply_thunk(t)
+        }
+    }
+}
+
+//find the thunk for variable i in an environment
+fn lookup<'a>(env: &Env<'a>, i: usize) -> Thunk<'a> {
+    let mut env = env;
+    for _ in 0..i {
+        env = &env.as_ref().unwrap().next;
+    }
+    env.as_ref().unwrap().thunk.clone()
+}
+
+//get the value of a thunk, evaluating it and updating the thunk if it is the first time
+fn force<'a>(t: &Thunk<'a>) -> Value<'a> {
+    let delay = std::mem::replace(&mut *t.borrow_mut(), Delay::Forcing);
+    let v = match delay {
+        Delay::Forced(v) => v,
+        Delay::Delayed((term, env)) => eval(term, env),
+        Delay::Forcing => panic!("Cannot evaluate, input depends on itself"),
+    };
+    *t.borrow_mut() = Delay::Forced(v.clone());
+    v
+}
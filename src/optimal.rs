@@ -0,0 +1,610 @@
+//! Optimal reduction of lambdas with interaction nets, using Lamping's algorithm
+
+use crate::{DeBruijn, Lambda};
+
+///Interaction net reducing a de Bruijn lambda to normal form with Lamping's optimal algorithm
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let d = DeBruijn::from(&lambda!("(%f.(%x.(f (f x)))) (%f.(%x.(f (f x))))"));
+///let mut net = InteractionNet::new(&d);
+///net.reduce();
+///println!("{}", net.read_back());
+/// //outputs (%x|y.(x (x (x (x y)))))
+///println!("{} beta steps, {} interactions", net.steps(), net.interactions());
+/// //outputs 5 beta steps, 77 interactions
+///```
+///
+///#### Net method:
+///
+///The lambda is turned into a graph of nodes, each with a principal port and some auxiliary ports:
+///abstraction nodes for functions, application nodes for reducibles, fan nodes where a variable is used more than once,
+///and bracket and croissant nodes around inputs and variables to keep track of how many inputs deep every node is.
+///Every node has a level, the number of inputs it is inside of.
+///
+///Two nodes joined by their principal ports interact:
+///nodes of the same kind and level cancel out, an abstraction and an application cancelling out is a step of beta reduction,
+///otherwise the node with the higher level is copied past the other one, with brackets and croissants raising or lowering its level.
+///
+///Fans are only copied one node at a time, so a reducible that is shared by several parts of the lambda is only ever reduced once,
+///even when it is inside a function that gets copied.
+///steps() counts these shared beta reduction steps, while interactions() also counts the work spent copying and keeping track of levels.
+///
+///The whole lambda is reduced, including inside functions, so reading back gives the normal form rather than the weak head normal form the machines stop at.
+pub struct InteractionNet {
+    nodes: Vec<Node>,
+    steps: usize,
+    interactions: usize,
+}
+
+//kinds of nodes, the root holds the port the lambda hangs from
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Kind {
+    Root,
+    Lambda,
+    Apply,
+    Fan,
+    Croissant,
+    Bracket,
+    Eraser,
+}
+
+//a port of a node, port 0 is the principal port
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Port {
+    node: usize,
+    port: usize,
+}
+
+//a node, the ports of a lambda are principal, output and variable,
+//and the ports of an application are principal (the function), output and input
+#[derive(Debug, Clone)]
+struct Node {
+    kind: Kind,
+    level: usize,
+    ports: [Port; 3],
+    alive: bool,
+}
+
+impl Kind {
+    //number of ports of the kind of node
+    fn arity(self) -> usize {
+        match self {
+            Kind::Root | Kind::Eraser => 1,
+            Kind::Croissant | Kind::Bracket => 2,
+            Kind::Lambda | Kind::Apply | Kind::Fan => 3,
+        }
+    }
+}
+
+//context used while reading back, a stack for every level
+type Context = Vec<Vec<Token>>;
+
+//an entry of a level of the context, the port a fan was entered by, a level added by a croissant, or two levels joined by a bracket
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Port(usize),
+    Marker,
+    Pair((Vec<Token>, Vec<Token>)),
+}
+
+//a node passed through while reading back, with the ports it was entered and left by
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Step {
+    node: usize,
+    from: usize,
+    to: usize,
+}
+
+impl InteractionNet {
+    ///Make a new net from a lambda
+    pub fn new(term: &DeBruijn) -> InteractionNet {
+        //tasks left to do, building a lambda at a level joined to a port, leaving a function,
+        //or starting and finishing an input, which puts brackets on the variables from outside of it
+        enum Task<'a> {
+            Build(&'a DeBruijn, usize, Port),
+            Unbind,
+            Enter,
+            Leave(usize),
+        }
+        let mut net = InteractionNet {
+            nodes: Vec::new(),
+            steps: 0,
+            interactions: 0,
+        };
+        let root = net.add(Kind::Root, 0);
+        //functions in scope, with the ports their variables need to be joined to
+        let mut binders: Vec<(usize, Vec<Port>)> = Vec::new();
+        //number of variables each function had when an input was entered
+        let mut entered: Vec<Vec<usize>> = Vec::new();
+        let mut tasks = vec![Task::Build(
+            term,
+            0,
+            Port {
+                node: root,
+                port: 0,
+            },
+        )];
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Build(DeBruijn::Variable(i), level, p) => {
+                    let c = net.add(Kind::Croissant, level);
+                    net.link(Port { node: c, port: 1 }, p);
+                    let b = binders.len() - 1 - i;
+                    binders[b].1.push(Port { node: c, port: 0 });
+                }
+                Task::Build(DeBruijn::Func(b), level, p) => {
+                    let l = net.add(Kind::Lambda, level);
+                    net.link(Port { node: l, port: 0 }, p);
+                    binders.push((l, Vec::new()));
+                    tasks.push(Task::Unbind);
+                    tasks.push(Task::Build(b, level, Port { node: l, port: 1 }));
+                }
+                Task::Build(DeBruijn::Reducible((a, b)), level, p) => {
+                    let n = net.add(Kind::Apply, level);
+                    net.link(Port { node: n, port: 1 }, p);
+                    tasks.push(Task::Leave(level));
+                    tasks.push(Task::Build(b, level + 1, Port { node: n, port: 2 }));
+                    tasks.push(Task::Enter);
+                    tasks.push(Task::Build(a, level, Port { node: n, port: 0 }));
+                }
+                Task::Unbind => {
+                    let (l, vars) = binders.pop().unwrap();
+                    let p = if vars.is_empty() {
+                        let e = net.add(Kind::Eraser, 0);
+                        Port { node: e, port: 0 }
+                    } else {
+                        let level = net.nodes[l].level;
+                        net.merge(vars, level)
+                    };
+                    net.link(Port { node: l, port: 2 }, p);
+                }
+                Task::Enter => entered.push(binders.iter().map(|b| b.1.len()).collect()),
+                Task::Leave(level) => {
+                    let counts = entered.pop().unwrap();
+                    for (b, n) in counts.into_iter().enumerate() {
+                        let outer = net.nodes[binders[b].0].level;
+                        //uses inside the input are joined by fans first if the function is at a lower level than the input,
+                        //so they only need one bracket
+                        if outer < level && binders[b].1.len() > n + 1 {
+                            let vars = binders[b].1.split_off(n);
+                            let v = net.merge(vars, outer);
+                            binders[b].1.push(v);
+                        }
+                        for v in &mut binders[b].1[n..] {
+                            let k = net.add(Kind::Bracket, level);
+                            net.link(Port { node: k, port: 1 }, *v);
+                            *v = Port { node: k, port: 0 };
+                        }
+                    }
+                }
+            }
+        }
+        net
+    }
+    //add a new node, with its ports not joined yet
+    fn add(&mut self, kind: Kind, level: usize) -> usize {
+        let none = Port { node: 0, port: 0 };
+        self.nodes.push(Node {
+            kind,
+            level,
+            ports: [none; 3],
+            alive: true,
+        });
+        self.nodes.len() - 1
+    }
+    //join two ports
+    fn link(&mut self, a: Port, b: Port) {
+        self.nodes[a.node].ports[a.port] = b;
+        self.nodes[b.node].ports[b.port] = a;
+    }
+    //get the port joined to a port
+    fn peer(&self, p: Port) -> Port {
+        self.nodes[p.node].ports[p.port]
+    }
+    //join uses of a variable with fans, returning the port joined to all of them
+    fn merge(&mut self, vars: Vec<Port>, level: usize) -> Port {
+        let mut out = vars[vars.len() - 1];
+        for v in vars[..vars.len() - 1].iter().rev() {
+            let f = self.add(Kind::Fan, level);
+            self.link(Port { node: f, port: 1 }, *v);
+            self.link(Port { node: f, port: 2 }, out);
+            out = Port { node: f, port: 0 };
+        }
+        out
+    }
+    ///Reduce the net until no nodes joined to the lambda can interact
+    ///
+    ///The net is followed from the root, doing every interaction found on the way and carrying on from just before it.
+    ///Functions are reduced before their inputs, so inputs that are thrown away are never reduced,
+    ///and a lambda without a normal form never stops reducing.
+    pub fn reduce(&mut self) {
+        while self.search(false) {}
+    }
+    ///Do the first interaction found following the net from the root, returning false if the net is already in normal form
+    pub fn step(&mut self) -> bool {
+        self.search(true)
+    }
+    //follow the net from the root, doing every interaction found, or only the first one, and return whether any were done
+    //
+    //the function of an application is followed before its input, so inputs are only searched once the function can no longer interact,
+    //and every port is only entered once, so parts of the net shared by fans are only searched once
+    fn search(&mut self, once: bool) -> bool {
+        let mut seen = vec![false; self.nodes.len() * 3];
+        let mut stack = vec![Port { node: 0, port: 0 }];
+        let mut done = false;
+        while let Some(start) = stack.pop() {
+            if !self.nodes[start.node].alive {
+                continue;
+            }
+            //ports left since the start, the path is carried on from the last one still in the net after an interaction
+            let mut trail = vec![start];
+            loop {
+                let from = trail[trail.len() - 1];
+                let p = self.peer(from);
+                if from.port == 0 && p.port == 0 && from.node != 0 {
+                    self.interact(from.node, p.node);
+                    done = true;
+                    if once {
+                        return true;
+                    }
+                    while trail.last().is_some_and(|p| !self.nodes[p.node].alive) {
+                        trail.pop();
+                    }
+                    if trail.is_empty() {
+                        break;
+                    }
+                    continue;
+                }
+                let i = p.node * 3 + p.port;
+                if seen.len() <= i {
+                    seen.resize(self.nodes.len() * 3, false);
+                }
+                if seen[i] {
+                    break;
+                }
+                seen[i] = true;
+                let to = match (self.nodes[p.node].kind, p.port) {
+                    (Kind::Lambda, 0) => {
+                        stack.push(Port {
+                            node: p.node,
+                            port: 1,
+                        });
+                        break;
+                    }
+                    //the function is followed straight away, after the input is left for later
+                    (Kind::Apply, 1) => {
+                        stack.push(Port {
+                            node: p.node,
+                            port: 2,
+                        });
+                        0
+                    }
+                    //both copies of a fan are searched
+                    (Kind::Fan, 0) => {
+                        stack.push(Port {
+                            node: p.node,
+                            port: 2,
+                        });
+                        stack.push(Port {
+                            node: p.node,
+                            port: 1,
+                        });
+                        break;
+                    }
+                    (Kind::Croissant | Kind::Bracket, 0) => 1,
+                    (Kind::Fan | Kind::Croissant | Kind::Bracket, _) => 0,
+                    _ => break,
+                };
+                trail.push(Port {
+                    node: p.node,
+                    port: to,
+                });
+            }
+        }
+        done
+    }
+    //make two nodes joined by their principal ports interact
+    fn interact(&mut self, a: usize, b: usize) {
+        self.interactions += 1;
+        let (x, y) = (&self.nodes[a], &self.nodes[b]);
+        if x.kind == Kind::Eraser {
+            self.erase(a, b);
+        } else if y.kind == Kind::Eraser {
+            self.erase(b, a);
+        } else if x.level == y.level {
+            if x.kind == Kind::Lambda && y.kind == Kind::Apply
+                || x.kind == Kind::Apply && y.kind == Kind::Lambda
+            {
+                self.steps += 1;
+            } else if x.kind != y.kind {
+                panic!("Cannot interact {:?} and {:?}", x.kind, y.kind);
+            }
+            self.annihilate(a, b);
+        } else if x.level < y.level {
+            self.commute(a, b);
+        } else {
+            self.commute(b, a);
+        }
+    }
+    //remove two nodes, joining their matching auxiliary ports
+    fn annihilate(&mut self, a: usize, b: usize) {
+        for r in 1..self.nodes[a].kind.arity() {
+            //read the ports again every time, in case the last join went through one of these nodes
+            let p = self.peer(Port { node: a, port: r });
+            let q = self.peer(Port { node: b, port: r });
+            self.link(p, q);
+        }
+        self.nodes[a].alive = false;
+        self.nodes[b].alive = false;
+    }
+    //copy the node with the higher level past the node with the lower level, and the other way around
+    fn commute(&mut self, low: usize, high: usize) {
+        let (l, h) = (self.nodes[low].clone(), self.nodes[high].clone());
+        let level = match l.kind {
+            Kind::Croissant => h.level - 1,
+            Kind::Bracket => h.level + 1,
+            _ => h.level,
+        };
+        let highs: Vec<usize> = (1..l.kind.arity())
+            .map(|_| self.add(h.kind, level))
+            .collect();
+        let lows: Vec<usize> = (1..h.kind.arity())
+            .map(|_| self.add(l.kind, l.level))
+            .collect();
+        //the auxiliary ports of each node become the principal ports of the copies of the other node
+        let map = |p: Port| {
+            if p.node == low {
+                Port {
+                    node: highs[p.port - 1],
+                    port: 0,
+                }
+            } else if p.node == high {
+                Port {
+                    node: lows[p.port - 1],
+                    port: 0,
+                }
+            } else {
+                p
+            }
+        };
+        for (r, n) in highs.iter().enumerate() {
+            self.link(Port { node: *n, port: 0 }, map(l.ports[r + 1]));
+        }
+        for (s, n) in lows.iter().enumerate() {
+            self.link(Port { node: *n, port: 0 }, map(h.ports[s + 1]));
+        }
+        for (r, n) in highs.iter().enumerate() {
+            for (s, m) in lows.iter().enumerate() {
+                self.link(
+                    Port {
+                        node: *n,
+                        port: s + 1,
+                    },
+                    Port {
+                        node: *m,
+                        port: r + 1,
+                    },
+                );
+            }
+        }
+        self.nodes[low].alive = false;
+        self.nodes[high].alive = false;
+    }
+    //remove a node with an eraser, putting erasers on its auxiliary ports
+    fn erase(&mut self, e: usize, n: usize) {
+        let node = self.nodes[n].clone();
+        let erasers: Vec<usize> = (1..node.kind.arity())
+            .map(|_| self.add(Kind::Eraser, 0))
+            .collect();
+        for (r, k) in erasers.iter().enumerate() {
+            let p = node.ports[r + 1];
+            let p = if p.node == n {
+                Port {
+                    node: erasers[p.port - 1],
+                    port: 0,
+                }
+            } else {
+                p
+            };
+            self.link(Port { node: *k, port: 0 }, p);
+        }
+        self.nodes[e].alive = false;
+        self.nodes[n].alive = false;
+    }
+    ///Number of beta reduction steps done so far, each one an abstraction and an application cancelling out
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+    ///Number of interactions done so far, including copying and removing nodes
+    pub fn interactions(&self) -> usize {
+        self.interactions
+    }
+    ///Read back the lambda the net represents
+    ///
+    ///The net is followed from the root, keeping a context of which copy of every fan the path is in,
+    ///so every part of the net shared by fans is copied into the lambda once for every use.
+    ///Reading back a net before reducing it gives back the lambda it was made from.
+    pub fn read_back(&self) -> Lambda {
+        //tasks left to do, following the net from a port with a context after cutting the path down to a length and adding a step,
+        //or building from the finished lambdas
+        enum Task {
+            Follow(Port, Context, usize, Option<Step>),
+            Func(usize),
+            Reducible,
+        }
+        //functions on the current path, with the position in the path they are at and their names
+        let mut binders: Vec<(usize, usize, usize)> = Vec::new();
+        let mut path: Vec<Step> = Vec::new();
+        let mut names = 0;
+        let mut tasks = vec![Task::Follow(self.nodes[0].ports[0], Vec::new(), 0, None)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(task) = tasks.pop() {
+            match task {
+                Task::Follow(mut p, mut ctx, len, step) => {
+                    path.truncate(len);
+                    binders.retain(|b| b.1 < len);
+                    path.extend(step);
+                    //follow nodes that only change the context, until reaching a function, variable or application
+                    loop {
+                        let node = &self.nodes[p.node];
+                        match (node.kind, p.port) {
+                            (Kind::Lambda, 0) => {
+                                binders.push((p.node, path.len(), names));
+                                path.push(Step {
+                                    node: p.node,
+                                    from: 0,
+                                    to: 1,
+                                });
+                                tasks.push(Task::Func(names));
+                                let q = self.peer(Port {
+                                    node: p.node,
+                                    port: 1,
+                                });
+                                tasks.push(Task::Follow(q, ctx, path.len(), None));
+                                names += 1;
+                                break;
+                            }
+                            (Kind::Lambda, 2) => {
+                                let n = self.find_binder(p.node, &binders, &path, ctx);
+                                out.push(Lambda::Variable(Lambda::get_name(n)));
+                                break;
+                            }
+                            (Kind::Apply, 1) => {
+                                tasks.push(Task::Reducible);
+                                //the input is followed after the function, from the same point of the path
+                                let step = Step {
+                                    node: p.node,
+                                    from: 1,
+                                    to: 2,
+                                };
+                                let q = self.peer(Port {
+                                    node: p.node,
+                                    port: 2,
+                                });
+                                tasks.push(Task::Follow(q, ctx.clone(), path.len(), Some(step)));
+                                let step = Step { to: 0, ..step };
+                                let q = self.peer(Port {
+                                    node: p.node,
+                                    port: 0,
+                                });
+                                tasks.push(Task::Follow(q, ctx, path.len(), Some(step)));
+                                break;
+                            }
+                            (Kind::Fan | Kind::Croissant | Kind::Bracket, _) => {
+                                let to = Self::pass(node, p.port, &mut ctx);
+                                path.push(Step {
+                                    node: p.node,
+                                    from: p.port,
+                                    to,
+                                });
+                                p = self.peer(Port {
+                                    node: p.node,
+                                    port: to,
+                                });
+                            }
+                            _ => panic!("Cannot read back {:?}", node.kind),
+                        }
+                    }
+                }
+                Task::Func(n) => {
+                    let b = out.pop().unwrap();
+                    out.push(Lambda::func(Lambda::get_name(n), b));
+                }
+                Task::Reducible => {
+                    let b = out.pop().unwrap();
+                    let a = out.pop().unwrap();
+                    out.push(a.attach(b));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+    //find the name of the function on the path a variable of a lambda node belongs to
+    //
+    //if the node is on the path more than once, the net is followed back up from the node with the context of the variable,
+    //until it leaves the path every other copy of the node was reached by
+    fn find_binder(
+        &self,
+        node: usize,
+        binders: &[(usize, usize, usize)],
+        path: &[Step],
+        mut ctx: Context,
+    ) -> usize {
+        let mut found: Vec<&(usize, usize, usize)> =
+            binders.iter().filter(|b| b.0 == node).collect();
+        let mut p = self.peer(Port { node, port: 0 });
+        let mut k = 1;
+        while found.len() > 1 {
+            let n = &self.nodes[p.node];
+            let to = match (n.kind, p.port) {
+                (Kind::Lambda, 1) => 0,
+                (Kind::Apply, 0 | 2) => 1,
+                (Kind::Fan | Kind::Croissant | Kind::Bracket, _) => Self::pass(n, p.port, &mut ctx),
+                _ => panic!("Cannot read back {:?}", n.kind),
+            };
+            found.retain(|b| {
+                b.1 >= k && {
+                    let s = path[b.1 - k];
+                    s.node == p.node && s.from == to && s.to == p.port
+                }
+            });
+            p = self.peer(Port {
+                node: p.node,
+                port: to,
+            });
+            k += 1;
+        }
+        match found.first() {
+            Some(b) => b.2,
+            None => panic!("Cannot read back, variable of {:?} has no function", node),
+        }
+    }
+    //pass through a fan, bracket or croissant entered by a port, changing the context, and return the port to leave by
+    fn pass(node: &Node, port: usize, ctx: &mut Context) -> usize {
+        let i = node.level;
+        if ctx.len() <= i + 1 {
+            ctx.resize(i + 2, Vec::new());
+        }
+        match (node.kind, port) {
+            (Kind::Fan, 0) => match ctx[i].pop() {
+                Some(Token::Port(r)) => r,
+                t => panic!("Cannot read back, no copy chosen for fan, found {:?}", t),
+            },
+            (Kind::Fan, r) => {
+                ctx[i].push(Token::Port(r));
+                0
+            }
+            (Kind::Croissant, 0) => {
+                ctx.remove(i);
+                1
+            }
+            (Kind::Croissant, _) => {
+                ctx.insert(i, vec![Token::Marker]);
+                0
+            }
+            (Kind::Bracket, 0) => {
+                let (a, b) = match ctx[i].pop() {
+                    Some(Token::Pair(p)) => p,
+                    //a level that was never joined is split into itself and an empty level
+                    t => {
+                        let mut a = std::mem::take(&mut ctx[i]);
+                        a.extend(t);
+                        (a, Vec::new())
+                    }
+                };
+                ctx[i] = a;
+                ctx.insert(i + 1, b);
+                1
+            }
+            (Kind::Bracket, _) => {
+                let b = ctx.remove(i + 1);
+                let a = std::mem::take(&mut ctx[i]);
+                ctx[i] = vec![Token::Pair((a, b))];
+                0
+            }
+            _ => unreachable!(),
+        }
+    }
+}
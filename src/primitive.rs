@@ -0,0 +1,275 @@
+//! Primitive constants and built-in operators, reduced by delta rules
+
+use crate::{Lambda, ParseError, Symbol};
+use std::fmt;
+use std::mem;
+
+///Primitive constant held by a lambda, reduced with delta rules by Lambda.evaluate()
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///println!("{}", lambda!("(if (== (* 6 7) 42) \"yes\" \"no\")").evaluate());
+/// //outputs "yes"
+///println!("{}", lambda!("(%f.((f 1) 2)) (%x|y.(- x y))").evaluate());
+/// //outputs -1
+///```
+///
+///#### Syntax:
+///
+///42 and -42 are integers, #t and #f are booleans and "hi" is a string, with \" and \\ for quotes and backslashes inside it.
+///
+///+, -, *, == and if are the built-in operators, see Operator.
+///A bracket starting with an operator applies it to every input after it, so (+ 1 2) is ((+ 1) 2).
+///
+///#### Delta rules:
+///
+///An operator applied to as many inputs as it takes is reduced in place of beta reduction, evaluating the inputs it needs first.
+///An operator applied to fewer inputs is a value and is not reduced, and an operator applied to inputs of the wrong type panics.
+///
+///Only Lambda.evaluate() and Strategy::Substitution apply delta rules, other strategies panic on lambdas with constants, or give EvalError::Constants with Lambda.try_evaluate_with().
+///Code generation, Program and TermArena only take pure lambdas and panic on constants too.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Constant {
+    ///Integer
+    Int(i64),
+    ///Boolean
+    Bool(bool),
+    ///String
+    Str(String),
+    ///Built-in operator
+    Op(Operator),
+    ///Native function registered with Environment.register_native(), put in place of its name by the environment
+    Native(Symbol),
+}
+
+///Built-in operator, reduced by a delta rule once it is applied to every input
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Operator {
+    ///+, adding two integers, wrapping on overflow
+    #[cfg_attr(feature = "serde", serde(rename = "+"))]
+    Add,
+    ///-, subtracting the second integer from the first, wrapping on overflow
+    #[cfg_attr(feature = "serde", serde(rename = "-"))]
+    Sub,
+    ///*, multiplying two integers, wrapping on overflow
+    #[cfg_attr(feature = "serde", serde(rename = "*"))]
+    Mul,
+    ///==, comparing two constants of the same type
+    #[cfg_attr(feature = "serde", serde(rename = "=="))]
+    Eq,
+    ///if, giving the second input if the first is #t and the third if it is #f
+    #[cfg_attr(feature = "serde", serde(rename = "if"))]
+    If,
+}
+
+impl Operator {
+    ///Number of inputs the operator is applied to before it is reduced
+    pub fn arity(self) -> usize {
+        match self {
+            Self::If => 3,
+            _ => 2,
+        }
+    }
+    //number of inputs evaluated before the operator is reduced, the first ones it takes
+    pub(crate) fn evaluated(self) -> usize {
+        match self {
+            Self::If => 1,
+            _ => 2,
+        }
+    }
+    //the token of the operator in the lambda syntax
+    fn token(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Eq => "==",
+            Self::If => "if",
+        }
+    }
+    //reduce the operator applied to its inputs
+    fn apply(self, mut inputs: Vec<Lambda>) -> Lambda {
+        //only the condition of if is evaluated, so the branch not taken never is
+        if self == Self::If {
+            return match inputs[0].take().recursive_evaluate() {
+                Lambda::Constant(Constant::Bool(true)) => inputs[1].take(),
+                Lambda::Constant(Constant::Bool(false)) => inputs[2].take(),
+                c => panic!("Cannot apply if to {}", c),
+            };
+        }
+        let a = inputs[0].take().recursive_evaluate();
+        let b = inputs[1].take().recursive_evaluate();
+        let c = match (self, &a, &b) {
+            (Self::Add, Lambda::Constant(Constant::Int(a)), Lambda::Constant(Constant::Int(b))) => {
+                Constant::Int(a.wrapping_add(*b))
+            }
+            (Self::Sub, Lambda::Constant(Constant::Int(a)), Lambda::Constant(Constant::Int(b))) => {
+                Constant::Int(a.wrapping_sub(*b))
+            }
+            (Self::Mul, Lambda::Constant(Constant::Int(a)), Lambda::Constant(Constant::Int(b))) => {
+                Constant::Int(a.wrapping_mul(*b))
+            }
+            (Self::Eq, Lambda::Constant(a), Lambda::Constant(b))
+                if mem::discriminant(a) == mem::discriminant(b)
+                    && !matches!(a, Constant::Op(_) | Constant::Native(_)) =>
+            {
+                Constant::Bool(a == b)
+            }
+            _ => panic!("Cannot apply {} to {} and {}", self, a, b),
+        };
+        Lambda::Constant(c)
+    }
+}
+
+impl Constant {
+    //parse a constant or operator token, or None if it is not one
+    pub(crate) fn parse(s: &str) -> Option<Constant> {
+        match s {
+            "#t" => Some(Self::Bool(true)),
+            "#f" => Some(Self::Bool(false)),
+            "+" => Some(Self::Op(Operator::Add)),
+            "-" => Some(Self::Op(Operator::Sub)),
+            "*" => Some(Self::Op(Operator::Mul)),
+            "==" => Some(Self::Op(Operator::Eq)),
+            "if" => Some(Self::Op(Operator::If)),
+            _ => {
+                //only an optional - followed by digits is an integer, so +1 is not one
+                let digits = s.strip_prefix('-').unwrap_or(s);
+                if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+                s.parse().ok().map(Self::Int)
+            }
+        }
+    }
+}
+
+impl Lambda {
+    //take the string constants out of lambda syntax, putting them into the inputs in place of a {}
+    pub(crate) fn extract_strings(s: &str, f: &mut Vec<Lambda>) -> Result<String, ParseError> {
+        let mut out = String::new();
+        //index of the next input, counting the {} before the string
+        let mut input = 0;
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let mut st = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some('\\') => match chars.next() {
+                                Some(e @ ('"' | '\\')) => st.push(e),
+                                _ => {
+                                    return Err(ParseError::Syntax(
+                                        "Syntax error, unknown escape in string".to_string(),
+                                    ));
+                                }
+                            },
+                            Some(c) => st.push(c),
+                            None => return Err(ParseError::Syntax("Unclosed string".to_string())),
+                        }
+                    }
+                    f.insert(input.min(f.len()), Lambda::Constant(Constant::Str(st)));
+                    input += 1;
+                    out.push_str("{}");
+                }
+                //record and match braces are not inputs
+                '{' => {
+                    if chars.peek() == Some(&'}') {
+                        input += 1;
+                    }
+                    out.push(c);
+                }
+                _ => out.push(c),
+            }
+        }
+        Ok(out)
+    }
+    //reduce the operator at the head of the lambda with its delta rule, keeping any inputs it does not take
+    pub(crate) fn delta(self) -> Lambda {
+        let mut inputs = Vec::new();
+        let mut head = self;
+        while let Lambda::Reducible(_) = head {
+            let (f, a) = head.into_pair();
+            inputs.push(a);
+            head = f;
+        }
+        inputs.reverse();
+        let op = match head {
+            Lambda::Constant(Constant::Op(op)) if inputs.len() >= op.arity() => op,
+            _ => panic!("Cannot reduce"),
+        };
+        let rest = inputs.split_off(op.arity());
+        let mut out = op.apply(inputs);
+        for a in rest {
+            out = out.attach(a);
+        }
+        out
+    }
+    //check if the lambda is an operator applied to fewer inputs than it takes, which cannot be reduced
+    pub(crate) fn is_unsaturated(&self) -> bool {
+        let mut inputs = 0;
+        let mut l = self;
+        while let Lambda::Reducible((a, _)) = l {
+            inputs += 1;
+            l = a;
+        }
+        matches!(l, Lambda::Constant(Constant::Op(op)) if inputs < op.arity())
+    }
+    //check if the lambda is an operator applied to every input it takes, reduced by Lambda.reduce() with its delta rule
+    pub(crate) fn is_saturated(&self) -> bool {
+        let mut l = self;
+        while let Lambda::Reducible((a, _)) = l {
+            l = a;
+        }
+        matches!(l, Lambda::Constant(Constant::Op(_))) && !self.is_unsaturated()
+    }
+    //check if the delta rule can be taken as a single step, the inputs the operator evaluates already being constants it takes
+    pub(crate) fn is_delta_step(&self) -> bool {
+        let mut inputs = Vec::new();
+        let mut l = self;
+        while let Lambda::Reducible((a, b)) = l {
+            inputs.push(&**b);
+            l = a;
+        }
+        inputs.reverse();
+        let op = match l {
+            Lambda::Constant(Constant::Op(op)) if inputs.len() >= op.arity() => *op,
+            _ => return false,
+        };
+        match (op, inputs[0], inputs[1]) {
+            (Operator::If, Lambda::Constant(Constant::Bool(_)), _) => true,
+            (Operator::If, _, _) => false,
+            (Operator::Eq, Lambda::Constant(a), Lambda::Constant(b)) => {
+                mem::discriminant(a) == mem::discriminant(b)
+                    && !matches!(a, Constant::Op(_) | Constant::Native(_))
+            }
+            (_, Lambda::Constant(Constant::Int(_)), Lambda::Constant(Constant::Int(_))) => {
+                op != Operator::Eq
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.token())
+    }
+}
+
+impl fmt::Display for Constant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{}", n),
+            Self::Bool(true) => write!(f, "#t"),
+            Self::Bool(false) => write!(f, "#f"),
+            Self::Str(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Self::Op(op) => write!(f, "{}", op),
+            Self::Native(name) => write!(f, "{}", name),
+        }
+    }
+}
@@ -0,0 +1,229 @@
+//! Serde serialization of lambdas, enabled with the `serde` feature
+//!
+//! Lambdas are serialized in a tagged format, with one tag per variant:
+//! ```rust
+//! use easy_lambda_calculus::*;
+//!
+//! let json = serde_json::to_string(&lambda!("%x.(x y)")).unwrap();
+//! println!("{}", json);
+//! //outputs {"func":{"var":"x","body":{"reducible":{"func":{"variable":"x"},"input":{"variable":"y"}}}}}
+//! let l: Lambda = serde_json::from_str(&json).unwrap();
+//! assert_eq!(l, lambda!("%x.(x y)"));
+//! ```
+//!
+//! The format is stable: the tags are variable, func, reducible and alpha_mark, a func has the fields var and body,
+//! and a reducible has the fields func and input.
+//! Constants have the tags int, bool, str, op and native, an op holding the token of the operator and a native the name of the function.
+
+use crate::{Constant, Lambda, Operator, Symbol};
+use serde::ser::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::mem;
+
+//tagged representation of a lambda, without the tokens only used while parsing
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Term {
+    Variable(String),
+    Func { var: String, body: Box<Term> },
+    Reducible { func: Box<Term>, input: Box<Term> },
+    AlphaMark(Box<Term>),
+    Int(i64),
+    Bool(bool),
+    Str(String),
+    Op(Operator),
+    Native(String),
+}
+
+impl Term {
+    //take the term, leaving a term without any terms inside it in its place
+    fn take(&mut self) -> Term {
+        mem::replace(self, Term::Bool(false))
+    }
+    //take the terms inside the term, putting them on the stack
+    fn take_inside(&mut self, stack: &mut Vec<Term>) {
+        match self {
+            Term::Func { body, .. } => stack.push(body.take()),
+            Term::Reducible { func, input } => {
+                stack.push(func.take());
+                stack.push(input.take());
+            }
+            Term::AlphaMark(a) => stack.push(a.take()),
+            _ => {}
+        }
+    }
+}
+
+//implement drop with a stack of terms left to drop, so deep terms do not overflow the stack
+impl Drop for Term {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.take_inside(&mut stack);
+        while let Some(mut t) = stack.pop() {
+            t.take_inside(&mut stack);
+        }
+    }
+}
+
+//convert with a stack of lambdas left to visit and a stack of finished terms, giving an error for lambdas that cannot be serialized
+impl TryFrom<&Lambda> for Term {
+    type Error = String;
+    fn try_from(l: &Lambda) -> Result<Term, String> {
+        enum Step<'a> {
+            Visit(&'a Lambda),
+            Func(String),
+            Reducible,
+            AlphaMark,
+        }
+        let mut stack = vec![Step::Visit(l)];
+        let mut out: Vec<Term> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Lambda::Variable(a)) => out.push(Term::Variable(a.to_string())),
+                Step::Visit(Lambda::Func((a, b))) => match &**a {
+                    Lambda::Variable(v) => {
+                        stack.push(Step::Func(v.to_string()));
+                        stack.push(Step::Visit(b));
+                    }
+                    a => return Err(format!("Cannot serialize function input {:?}", a)),
+                },
+                Step::Visit(Lambda::Reducible((a, b))) => {
+                    stack.push(Step::Reducible);
+                    stack.push(Step::Visit(b));
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::AlphaMark(a)) => {
+                    stack.push(Step::AlphaMark);
+                    stack.push(Step::Visit(a));
+                }
+                Step::Visit(Lambda::Constant(c)) => out.push(match c {
+                    Constant::Int(n) => Term::Int(*n),
+                    Constant::Bool(b) => Term::Bool(*b),
+                    Constant::Str(st) => Term::Str(st.clone()),
+                    Constant::Op(op) => Term::Op(*op),
+                    Constant::Native(name) => Term::Native(name.to_string()),
+                }),
+                Step::Visit(l) => return Err(format!("Cannot serialize {:?}", l)),
+                Step::Func(var) => {
+                    let body = Box::new(out.pop().unwrap());
+                    out.push(Term::Func { var, body });
+                }
+                Step::Reducible => {
+                    let input = Box::new(out.pop().unwrap());
+                    let func = Box::new(out.pop().unwrap());
+                    out.push(Term::Reducible { func, input });
+                }
+                Step::AlphaMark => {
+                    let a = Box::new(out.pop().unwrap());
+                    out.push(Term::AlphaMark(a));
+                }
+            }
+        }
+        Ok(out.pop().unwrap())
+    }
+}
+
+//convert with a stack of terms left to visit and a stack of finished lambdas
+impl From<Term> for Lambda {
+    fn from(t: Term) -> Lambda {
+        enum Step {
+            Visit(Term),
+            Func(String),
+            Reducible,
+            AlphaMark,
+        }
+        let mut stack = vec![Step::Visit(t)];
+        let mut out: Vec<Lambda> = Vec::new();
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(mut t) => match &mut t {
+                    Term::Variable(a) => out.push(Lambda::Variable(Symbol::new(a))),
+                    Term::Func { var, body } => {
+                        stack.push(Step::Func(mem::take(var)));
+                        stack.push(Step::Visit(body.take()));
+                    }
+                    Term::Reducible { func, input } => {
+                        stack.push(Step::Reducible);
+                        stack.push(Step::Visit(input.take()));
+                        stack.push(Step::Visit(func.take()));
+                    }
+                    Term::AlphaMark(a) => {
+                        stack.push(Step::AlphaMark);
+                        stack.push(Step::Visit(a.take()));
+                    }
+                    Term::Int(n) => out.push(Lambda::Constant(Constant::Int(*n))),
+                    Term::Bool(b) => out.push(Lambda::Constant(Constant::Bool(*b))),
+                    Term::Str(st) => out.push(Lambda::Constant(Constant::Str(mem::take(st)))),
+                    Term::Op(op) => out.push(Lambda::Constant(Constant::Op(*op))),
+                    Term::Native(name) => {
+                        out.push(Lambda::Constant(Constant::Native(Symbol::new(name))))
+                    }
+                },
+                Step::Func(var) => {
+                    let body = out.pop().unwrap();
+                    out.push(Lambda::func(&var, body));
+                }
+                Step::Reducible => {
+                    let input = out.pop().unwrap();
+                    let func = out.pop().unwrap();
+                    out.push(func.attach(input));
+                }
+                Step::AlphaMark => {
+                    let a = out.pop().unwrap();
+                    out.push(Lambda::AlphaMark(Box::new(a)));
+                }
+            }
+        }
+        out.pop().unwrap()
+    }
+}
+
+impl Serialize for Lambda {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Term::try_from(self)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Lambda {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Lambda, D::Error> {
+        Term::deserialize(deserializer).map(Lambda::from)
+    }
+}
+
+///Serialize a lambda as a string in the `lambda!()` syntax, for use with `#[serde(with = "...")]`
+///
+///```rust
+///use easy_lambda_calculus::*;
+///use serde::{Deserialize, Serialize};
+///
+///#[derive(Serialize, Deserialize)]
+///struct Config {
+///    #[serde(with = "easy_lambda_calculus::serialize::string")]
+///    program: Lambda,
+///}
+///
+///let c: Config = serde_json::from_str(r#"{"program": "(%x|y.x) (%z.z)"}"#).unwrap();
+///println!("{}", serde_json::to_string(&c).unwrap());
+/// //outputs {"program":"((%x|y.x) (%z.z))"}
+///```
+///
+///Strings that are not valid lambdas give a deserialization error, parsed with Lambda::try_new().
+pub mod string {
+    use crate::Lambda;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    ///Serialize a lambda as its `Display` string
+    pub fn serialize<S: Serializer>(l: &Lambda, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(l)
+    }
+
+    ///Deserialize a lambda from a string in the `lambda!()` syntax
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Lambda, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Lambda::try_new(&s, vec![])
+            .map_err(|e| D::Error::custom(format!("invalid lambda {:?}: {}", s, e)))
+    }
+}
@@ -0,0 +1,950 @@
+//! Surface syntax desugared into plain lambdas before parsing
+
+use crate::{FromLambda, Lambda, ListEncoding, Numeral, ParseError};
+
+//the Z combinator, a fixed point combinator that also works when inputs are evaluated before they are applied
+const FIX: &str = "&(%f.((%x.(f (%v.((x x) v)))) (%x.(f (%v.((x x) v))))))";
+//applies its second input to its first, so the definition can come before the body and keep the order of any {}
+const THEN: &str = "&(%a|k.(k a))";
+//ends the nested pairs of a tuple, and takes the second lambda of a pair
+const NIL: &str = "&(%x|y.y)";
+//takes the first lambda of a pair
+const FIRST: &str = "&(%x|y.x)";
+//words the sugar reads as keywords, which cannot be variable names
+const KEYWORDS: [&str; 4] = ["data", "match", "letrec", "in"];
+
+//constructor of a data type declared with data
+struct Constructor {
+    name: String,
+    data: String,
+    index: usize,
+    fields: usize,
+}
+
+///Options for parsing a lambda, see Lambda::parse_with()
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseOptions {
+    ///Encoding of numeral literals, eg: #5
+    pub numeral: Numeral,
+    ///Encoding of list literals, eg: [a, b, c]
+    pub list: ListEncoding,
+}
+
+impl Default for ParseOptions {
+    fn default() -> ParseOptions {
+        ParseOptions {
+            numeral: Numeral::Church,
+            list: ListEncoding::Church,
+        }
+    }
+}
+
+//sugared form of a lambda, written by the alternate display
+pub(crate) enum Sugar<'a> {
+    //Church numeral
+    Numeral(u64),
+    //tuple of the lambdas in its nested pairs
+    Tuple(Vec<&'a Lambda>),
+    //Church or Scott list of its items
+    List(Vec<&'a Lambda>),
+    //name of a definition, applied to the inputs it was given
+    Applied(String, Vec<&'a Lambda>),
+}
+
+impl Lambda {
+    //find the sugared form of the lambda, if it has the shape of an encoding the syntax has sugar for
+    pub(crate) fn sugar(&self) -> Option<Sugar<'_>> {
+        //marks are left out of the sugared form, as the syntax puts them around every literal
+        let mut l = self;
+        while let Self::AlphaMark(a) = l {
+            l = a;
+        }
+        if let Ok(n) = u64::from_lambda(l) {
+            return Some(Sugar::Numeral(n));
+        }
+        l.sugar_tuple()
+            .or_else(|| l.sugar_church_list())
+            .or_else(|| l.sugar_scott_list())
+    }
+    //find the items of a tuple
+    fn sugar_tuple(&self) -> Option<Sugar<'_>> {
+        //a tuple is %f.((f a) b) with b another one, ending with %x|y.y
+        let mut pairs: Vec<(&Lambda, &Lambda)> = Vec::new();
+        let mut l = self;
+        while let Self::Func((f, b)) = l
+            && let Self::Reducible((c, rest)) = &**b
+            && let Self::Reducible((g, item)) = &**c
+            && f == g
+        {
+            pairs.push((&**f, &**item));
+            l = rest;
+            //the pairs written by the syntax are marked for alpha reduction
+            while let Self::AlphaMark(a) = l {
+                l = a;
+            }
+        }
+        let nil = match l {
+            Self::Func((x, b)) => matches!(&**b, Self::Func((y, c)) if x != y && c == y),
+            _ => false,
+        };
+        if pairs.is_empty() || !nil {
+            return None;
+        }
+        //the binder of every pair cannot be used by its item or the items after it, until a pair binds the same name
+        for (i, (f, _)) in pairs.iter().enumerate() {
+            for (j, (g, item)) in pairs.iter().enumerate().skip(i) {
+                if j > i && g == f {
+                    break;
+                }
+                if is_free(item, f) {
+                    return None;
+                }
+            }
+        }
+        Some(Sugar::Tuple(
+            pairs.into_iter().map(|(_, item)| item).collect(),
+        ))
+    }
+    //find the items of a Church list, %c|n.((c a) ((c b) n)) with c and n not used by the items
+    fn sugar_church_list(&self) -> Option<Sugar<'_>> {
+        let (c, n, mut l) = match self {
+            Self::Func((c, b)) => match &**b {
+                Self::Func((n, l)) if c != n => (&**c, &**n, &**l),
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let mut items = Vec::new();
+        while let Self::Reducible((a, rest)) = l
+            && let Self::Reducible((f, item)) = &**a
+            && **f == *c
+        {
+            if is_free(item, c) || is_free(item, n) {
+                return None;
+            }
+            items.push(&**item);
+            l = rest;
+        }
+        if items.is_empty() || *l != *n {
+            return None;
+        }
+        Some(Sugar::List(items))
+    }
+    //find the items of a Scott list, %n|c.((c a) rest) with rest another one, ending with %n|c.n
+    fn sugar_scott_list(&self) -> Option<Sugar<'_>> {
+        let mut items = Vec::new();
+        let mut l = self;
+        loop {
+            let (n, c, b) = match l {
+                Self::Func((n, b)) => match &**b {
+                    Self::Func((c, b)) if n != c => (&**n, &**c, &**b),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            if *b == *n && !items.is_empty() {
+                return Some(Sugar::List(items));
+            }
+            match b {
+                Self::Reducible((a, rest))
+                    if let Self::Reducible((f, item)) = &**a
+                        && **f == *c
+                        && !is_free(item, n)
+                        && !is_free(item, c)
+                        && rest.is_closed() =>
+                {
+                    items.push(&**item);
+                    l = rest;
+                    while let Self::AlphaMark(a) = l {
+                        l = a;
+                    }
+                }
+                _ => return None,
+            }
+        }
+    }
+    //rewrite every numeral literal #n into the encoding the options give
+    //numerals are put into the inputs in place of a {}, so they are not parsed again
+    pub(crate) fn desugar_numerals(
+        s: &str,
+        f: &mut Vec<Lambda>,
+        options: &ParseOptions,
+    ) -> Result<String, ParseError> {
+        let chars: Vec<char> = s.chars().collect();
+        let mut out = String::new();
+        //index of the next input, counting the {} before the numeral
+        let mut input = 0;
+        let mut i = 0;
+        while i < chars.len() {
+            let digits: String = chars[i + 1..]
+                .iter()
+                .take_while(|c| c.is_ascii_digit())
+                .collect();
+            if chars[i] == '#' && !digits.is_empty() {
+                let n = match digits.parse() {
+                    Ok(n) => n,
+                    Err(_) => {
+                        return Err(ParseError::Syntax(format!(
+                            "Syntax error, numeral #{} is too big",
+                            digits
+                        )));
+                    }
+                };
+                f.insert(input.min(f.len()), options.numeral.encode(n));
+                input += 1;
+                out.push_str("&{}");
+                i += 1 + digits.len();
+                continue;
+            }
+            if chars[i] == '{' && chars.get(i + 1) == Some(&'}') {
+                input += 1;
+            }
+            out.push(chars[i]);
+            i += 1;
+        }
+        Ok(out)
+    }
+    //rewrite every list literal [a, b] into the encoding the options give, after every other sugar
+    //the items are parsed on their own and the list put into the inputs in place of a {}, so the list is not parsed again
+    pub(crate) fn desugar_lists(
+        s: &str,
+        f: &mut Vec<Lambda>,
+        options: &ParseOptions,
+    ) -> Result<String, ParseError> {
+        let mut out = String::new();
+        //index of the next input, counting the {} before it
+        let mut input = 0;
+        //start of every list left open in the output, with the index of its first input
+        let mut lists: Vec<(usize, usize)> = Vec::new();
+        let mut chars = s.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'}') => {
+                    input += 1;
+                    out.push(c);
+                }
+                '[' => lists.push((out.len(), input)),
+                //the list closed is the innermost one, so the lists inside it are already inputs
+                ']' if !lists.is_empty() => {
+                    let (start, first) = lists.pop().unwrap();
+                    let inside = out.split_off(start);
+                    let inputs: Vec<Lambda> =
+                        f.drain(first.min(f.len())..input.min(f.len())).collect();
+                    f.insert(
+                        first.min(f.len()),
+                        Self::desugar_list(&inside, inputs, options)?,
+                    );
+                    input = first + 1;
+                    out.push_str("{}");
+                }
+                _ => out.push(c),
+            }
+        }
+        if !lists.is_empty() {
+            return Err(ParseError::Syntax(
+                "Syntax error, unclosed list".to_string(),
+            ));
+        }
+        Ok(out)
+    }
+    //make the list encoding of the items of a list literal, with the inputs of the items
+    fn desugar_list(
+        inside: &str,
+        inputs: Vec<Lambda>,
+        options: &ParseOptions,
+    ) -> Result<Lambda, ParseError> {
+        let mut items = split_top(inside, ',');
+        if items.len() == 1 && items[0].trim().is_empty() {
+            items.clear();
+        }
+        if let Some(item) = items.iter().find(|i| i.trim().is_empty()) {
+            return Err(ParseError::Syntax(format!(
+                "Syntax error, empty item {:?} in list [{}]",
+                item, inside
+            )));
+        }
+        let mut inputs = inputs.into_iter();
+        let items: Vec<Lambda> = items
+            .iter()
+            .map(|i| {
+                Self::parse_core(
+                    i.trim(),
+                    inputs.by_ref().take(i.matches("{}").count()).collect(),
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        //the items are not marked for alpha reduction, so the names of the list must not capture their variables
+        let free: Vec<String> = items.iter().flat_map(|i| i.free_vars()).collect();
+        let names = fresh_names("", &free, 2);
+        let (n, c) = (names[0].as_str(), names[1].as_str());
+        Ok(match options.list {
+            ListEncoding::Church => {
+                let mut out = Self::var(n);
+                for item in items.into_iter().rev() {
+                    out = Self::var(c).attach(item).attach(out);
+                }
+                Self::func(c, Self::func(n, out))
+            }
+            ListEncoding::Scott => {
+                let mut out = Self::AlphaMark(Box::new(ListEncoding::Scott.nil()));
+                for item in items.into_iter().rev() {
+                    out = Self::func(n, Self::func(c, Self::var(c).attach(item).attach(out)));
+                }
+                out
+            }
+        })
+    }
+    //rewrite every data declaration, match and constructor into Scott encoded lambdas
+    //inputs holds the free variables of the inputs, which the cases must not capture
+    pub(crate) fn desugar_data(s: &str, inputs: &[String]) -> Result<String, ParseError> {
+        let mut chars: Vec<char> = s.chars().collect();
+        let mut constructors: Vec<Constructor> = Vec::new();
+        //the declarations are removed, their constructors being used anywhere in the lambda
+        while let Some(start) = (0..chars.len()).find(|i| declaration_at(&chars, *i)) {
+            let end = match chars[start..].iter().position(|c| *c == ';') {
+                Some(e) => start + e,
+                None => {
+                    return Err(ParseError::Syntax(
+                        "Syntax error, expected ; after data declaration".to_string(),
+                    ));
+                }
+            };
+            let decl: String = chars[start + 4..end].iter().collect();
+            Self::declare(&decl, &mut constructors)?;
+            chars.drain(start..skip_spaces(&chars, end + 1));
+        }
+        if constructors.is_empty() {
+            return Ok(chars.into_iter().collect());
+        }
+        while let Some(start) = (0..chars.len()).find(|i| keyword_at(&chars, *i, "match")) {
+            Self::desugar_match(&mut chars, start, inputs, &constructors)?;
+        }
+        //a bracket starting with a constructor applies it to every input after it
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == '(' {
+                Self::desugar_constructor_application(&mut chars, i, &constructors);
+            }
+            i += 1;
+        }
+        let mut out = String::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let word: String = chars[i..]
+                .iter()
+                .take_while(|c| c.is_alphanumeric())
+                .collect();
+            if word.is_empty() {
+                out.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            match constructors.iter().find(|c| c.name == word) {
+                Some(c) => {
+                    let count = constructors.iter().filter(|d| d.data == c.data).count();
+                    let l = Self::constructor_function(count, c.index, &vec![false; c.fields]);
+                    out.push_str(&format!("&{}", l));
+                }
+                None => out.push_str(&word),
+            }
+            i += word.chars().count();
+        }
+        Ok(out)
+    }
+    //add the constructors of a data declaration, written as Name = A x | B x y
+    fn declare(decl: &str, constructors: &mut Vec<Constructor>) -> Result<(), ParseError> {
+        let (data, alternatives) = match decl.split_once('=') {
+            Some((d, a)) => (d.trim(), a),
+            None => {
+                return Err(ParseError::Syntax(
+                    "Syntax error, expected = in data declaration".to_string(),
+                ));
+            }
+        };
+        if !data.starts_with(|c: char| c.is_uppercase()) {
+            return Err(ParseError::Syntax(format!(
+                "Syntax error, data type {} must start with an uppercase letter",
+                data
+            )));
+        }
+        for (index, alt) in alternatives.split('|').enumerate() {
+            let mut words = alt.split_whitespace();
+            let name = match words.next() {
+                Some(n) if n.starts_with(|c: char| c.is_uppercase()) => n.to_string(),
+                Some(n) => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, constructor {} must start with an uppercase letter",
+                        n
+                    )));
+                }
+                None => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, empty constructor in data {}",
+                        data
+                    )));
+                }
+            };
+            if constructors.iter().any(|c| c.name == name) {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, constructor {} is declared twice",
+                    name
+                )));
+            }
+            constructors.push(Constructor {
+                name,
+                data: data.to_string(),
+                index,
+                fields: words.count(),
+            });
+        }
+        Ok(())
+    }
+    //rewrite the match starting at the index into the matched lambda applied to one function for every constructor
+    fn desugar_match(
+        chars: &mut Vec<char>,
+        start: usize,
+        inputs: &[String],
+        constructors: &[Constructor],
+    ) -> Result<(), ParseError> {
+        //the cases start at the first { that is not a {} input
+        let open = (start + 5..chars.len())
+            .find(|i| chars[*i] == '{' && chars.get(i + 1) != Some(&'}'))
+            .ok_or_else(|| {
+                ParseError::Syntax("Syntax error, expected { after match".to_string())
+            })?;
+        let mut depth = 0;
+        let close = (open..chars.len())
+            .find(|i| {
+                match chars[*i] {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            })
+            .ok_or_else(|| ParseError::Syntax("Syntax error, unclosed match".to_string()))?;
+        let scrutinee: String = chars[start + 5..open].iter().collect();
+        let cases: String = chars[open + 1..close].iter().collect();
+        let mut handlers: Vec<(String, Vec<String>, String)> = Vec::new();
+        let mut default = None;
+        for case in split_top(&cases, ',') {
+            let (pattern, body) = match case.split_once("->") {
+                Some((p, b)) => (p, b.trim().to_string()),
+                None => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, expected -> in match case {}",
+                        case.trim()
+                    )));
+                }
+            };
+            let mut words = pattern.split_whitespace().map(|w| w.to_string());
+            match words.next() {
+                Some(w) if w == "_" => default = Some(body),
+                Some(w) => handlers.push((w, words.collect(), body)),
+                None => {
+                    return Err(ParseError::Syntax(
+                        "Syntax error, empty pattern in match".to_string(),
+                    ));
+                }
+            }
+        }
+        let data = match handlers.first() {
+            Some((name, _, _)) => match constructors.iter().find(|c| c.name == *name) {
+                Some(c) => &c.data,
+                None => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, unknown constructor {}",
+                        name
+                    )));
+                }
+            },
+            None => {
+                return Err(ParseError::Syntax(
+                    "Syntax error, match needs a case with a constructor".to_string(),
+                ));
+            }
+        };
+        //a case holding a {} is bound once in the order it is written, so the inputs keep their order
+        let bind = cases.contains("{}");
+        let names = fresh_names(
+            &format!("{} {}", scrutinee, cases),
+            inputs,
+            handlers.len() + 2,
+        );
+        let default_name = &names[handlers.len()];
+        let mut out = format!("({})", scrutinee.trim());
+        for c in constructors.iter().filter(|c| c.data == *data) {
+            let mut found = handlers
+                .iter()
+                .enumerate()
+                .filter(|(_, (n, _, _))| *n == c.name);
+            let handler = match (found.next(), &default) {
+                (Some((i, (_, vars, body))), _) => {
+                    if vars.len() != c.fields {
+                        return Err(ParseError::Syntax(format!(
+                            "Syntax error, constructor {} has {} fields, found {}",
+                            c.name,
+                            c.fields,
+                            vars.len()
+                        )));
+                    }
+                    match (bind, vars.is_empty()) {
+                        (true, _) => names[i].clone(),
+                        (false, true) => format!("({})", body),
+                        (false, false) => format!("(%{}.({}))", vars.join("|"), body),
+                    }
+                }
+                //the fields are ignored, so they get names the body does not use
+                (None, Some(body)) => {
+                    let body = if bind { default_name } else { body };
+                    match c.fields {
+                        0 => format!("({})", body),
+                        n => format!("(%{}.({}))", fresh_names(body, inputs, n).join("|"), body),
+                    }
+                }
+                (None, None) => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, match is missing the {} case",
+                        c.name
+                    )));
+                }
+            };
+            if found.next().is_some() {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, match has the {} case twice",
+                    c.name
+                )));
+            }
+            out = format!("({} {})", out, handler);
+        }
+        if bind {
+            let mut bound: Vec<&str> = names[..handlers.len()].iter().map(|n| n.as_str()).collect();
+            if default.is_some() {
+                bound.push(default_name);
+            }
+            out = format!("(%{}.{})", bound.join("|"), out);
+            for (_, vars, body) in &handlers {
+                match vars.is_empty() {
+                    true => out = format!("({} ({}))", out, body),
+                    false => out = format!("({} (%{}.({})))", out, vars.join("|"), body),
+                }
+            }
+            if let Some(body) = &default {
+                out = format!("({} ({}))", out, body);
+            }
+        }
+        if let Some((name, _, _)) = handlers
+            .iter()
+            .find(|(n, _, _)| !constructors.iter().any(|c| c.name == *n && c.data == *data))
+        {
+            return Err(ParseError::Syntax(format!(
+                "Syntax error, {} is not a constructor of {}",
+                name, data
+            )));
+        }
+        chars.splice(start..=close, out.chars());
+        Ok(())
+    }
+    //rewrite the bracket at the index into nested reducibles if it starts with a constructor applied to several inputs
+    fn desugar_constructor_application(
+        chars: &mut Vec<char>,
+        open: usize,
+        constructors: &[Constructor],
+    ) {
+        let mut depth = 0;
+        let close = match (open..chars.len()).find(|i| {
+            match chars[*i] {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth == 0
+        }) {
+            Some(c) => c,
+            None => return,
+        };
+        let inside: String = chars[open + 1..close].iter().collect();
+        let items = split_top(&inside, ' ');
+        let items: Vec<&str> = items
+            .iter()
+            .map(|i| i.trim())
+            .filter(|i| !i.is_empty())
+            .collect();
+        if items.len() < 3 || !constructors.iter().any(|c| c.name == items[0]) {
+            return;
+        }
+        let mut out = items[0].to_string();
+        for i in &items[1..] {
+            out = format!("({} {})", out, i);
+        }
+        chars.splice(open..=close, out.chars());
+    }
+    //rewrite every record into a tuple, every tuple into nested pairs and every projection into the pairs applied to selectors
+    //inputs holds the free variables of the inputs, which the pairs must not capture
+    pub(crate) fn desugar_tuples(s: &str, inputs: &[String]) -> Result<String, ParseError> {
+        let mut chars: Vec<char> = s.chars().collect();
+        //the sorted fields of every record, to find the place of a field from its name
+        let mut records: Vec<Vec<String>> = Vec::new();
+        //a { that is not a {} starts a record, the last one being the innermost
+        while let Some(open) =
+            (0..chars.len()).rfind(|i| chars[*i] == '{' && chars.get(i + 1) != Some(&'}'))
+        {
+            let mut depth = 0;
+            let close = (open..chars.len())
+                .find(|i| {
+                    match chars[*i] {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .ok_or_else(|| ParseError::Syntax("Syntax error, unclosed record".to_string()))?;
+            let inside: String = chars[open + 1..close].iter().collect();
+            let out = Self::desugar_record(&inside, inputs, &mut records)?;
+            chars.splice(open..=close, out.chars());
+        }
+        //the last < is the innermost tuple, so the first > after it closes it
+        while let Some(open) = (0..chars.len()).rfind(|i| chars[*i] == '<') {
+            let close = match (open..chars.len()).find(|i| chars[*i] == '>') {
+                Some(c) => c,
+                None => {
+                    return Err(ParseError::Syntax(
+                        "Syntax error, unclosed tuple".to_string(),
+                    ));
+                }
+            };
+            let inside: String = chars[open + 1..close].iter().collect();
+            let mut items = split_top(&inside, ',');
+            if items.len() == 1 && items[0].trim().is_empty() {
+                items.clear();
+            }
+            let f = &fresh_names(&inside, inputs, 1)[0];
+            let mut out = NIL.to_string();
+            for item in items.iter().rev() {
+                if item.trim().is_empty() {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, empty item in tuple <{}>",
+                        inside
+                    )));
+                }
+                out = format!("(%{}.(({} ({})) {}))", f, f, item.trim(), out);
+            }
+            chars.splice(open..=close, out.chars());
+        }
+        let mut i = 0;
+        while i < chars.len() {
+            match chars[i] {
+                //the dot after the inputs of a function is not a projection
+                '%' => {
+                    i = (i..chars.len())
+                        .find(|j| chars[*j] == '.')
+                        .unwrap_or(chars.len());
+                }
+                '.' => i = Self::desugar_projection(&mut chars, i, &records)?,
+                _ => {}
+            }
+            i += 1;
+        }
+        Ok(chars.into_iter().collect())
+    }
+    //rewrite the inside of a record, written as name = e, into a tuple of the values sorted by field name
+    fn desugar_record(
+        inside: &str,
+        inputs: &[String],
+        records: &mut Vec<Vec<String>>,
+    ) -> Result<String, ParseError> {
+        let mut fields: Vec<(String, String)> = Vec::new();
+        for field in split_top(inside, ',') {
+            let (name, value) = match field.split_once('=') {
+                Some((n, v)) => (n.trim().to_string(), v.trim().to_string()),
+                None => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, expected = in record field {}",
+                        field.trim()
+                    )));
+                }
+            };
+            if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric()) {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, invalid record field name {}",
+                    name
+                )));
+            }
+            if fields.iter().any(|(n, _)| *n == name) {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, record has the field {} twice",
+                    name
+                )));
+            }
+            fields.push((name, value));
+        }
+        let mut sorted: Vec<usize> = (0..fields.len()).collect();
+        sorted.sort_by(|a, b| fields[*a].0.cmp(&fields[*b].0));
+        records.push(sorted.iter().map(|i| fields[*i].0.clone()).collect());
+        //values holding a {} are bound in the order they are written, so the inputs keep their order
+        if inside.contains("{}") && sorted.iter().enumerate().any(|(i, j)| i != *j) {
+            let names = fresh_names(inside, inputs, fields.len());
+            let values: Vec<&str> = sorted.iter().map(|i| names[*i].as_str()).collect();
+            let mut out = format!("(%{}.<{}>)", names.join("|"), values.join(", "));
+            for (_, value) in &fields {
+                out = format!("({} ({}))", out, value);
+            }
+            Ok(out)
+        } else {
+            let values: Vec<&str> = sorted.iter().map(|i| fields[*i].1.as_str()).collect();
+            Ok(format!("<{}>", values.join(", ")))
+        }
+    }
+    //rewrite the projection at the dot into the selectors taking the field out of the pairs, giving the index to go on from
+    fn desugar_projection(
+        chars: &mut Vec<char>,
+        dot: usize,
+        records: &[Vec<String>],
+    ) -> Result<usize, ParseError> {
+        let field: String = chars[dot + 1..]
+            .iter()
+            .take_while(|c| c.is_alphanumeric())
+            .collect();
+        if field.is_empty() {
+            return Err(ParseError::Syntax(
+                "Syntax error, expected a field after .".to_string(),
+            ));
+        }
+        //the projected lambda is the bracket, input or variable right before the dot
+        let mut start = dot;
+        if matches!(chars.get(dot.wrapping_sub(1)), Some(')' | '}')) {
+            let mut depth = 0;
+            while start > 0 {
+                start -= 1;
+                match chars[start] {
+                    ')' | '}' => depth += 1,
+                    '(' | '{' => depth -= 1,
+                    _ => {}
+                }
+                if depth == 0 {
+                    break;
+                }
+            }
+        } else {
+            while start > 0 && chars[start - 1].is_alphanumeric() {
+                start -= 1;
+            }
+        }
+        if start > 0 && chars[start - 1] == '&' {
+            start -= 1;
+        }
+        if start == dot {
+            return Err(ParseError::Syntax(format!(
+                "Syntax error, expected a lambda before .{}",
+                field
+            )));
+        }
+        let index = match field.parse::<usize>() {
+            Ok(n) => n,
+            Err(_) => {
+                let mut places = records
+                    .iter()
+                    .filter_map(|r| r.iter().position(|n| *n == field));
+                match places.next() {
+                    Some(n) if places.all(|m| m == n) => n,
+                    Some(_) => {
+                        return Err(ParseError::Syntax(format!(
+                            "Syntax error, field {} is not at the same place in every record",
+                            field
+                        )));
+                    }
+                    None => {
+                        return Err(ParseError::Syntax(format!(
+                            "Syntax error, unknown field {}",
+                            field
+                        )));
+                    }
+                }
+            }
+        };
+        let mut out: String = chars[start..dot].iter().collect();
+        for _ in 0..index {
+            out = format!("({} {})", out, NIL);
+        }
+        out = format!("({} {})", out, FIRST);
+        let len = out.chars().count();
+        chars.splice(start..dot + 1 + field.chars().count(), out.chars());
+        Ok(start + len - 1)
+    }
+    //rewrite every letrec f = e in b into ((THEN (FIX (%f.e))) (%f.b))
+    pub(crate) fn desugar_letrec(s: &str) -> Result<String, ParseError> {
+        let mut chars: Vec<char> = s.chars().collect();
+        while let Some(start) = (0..chars.len()).find(|i| keyword_at(&chars, *i, "letrec")) {
+            let mut i = skip_spaces(&chars, start + 6);
+            let name: String = chars[i..]
+                .iter()
+                .take_while(|c| Self::ALPH.contains(**c))
+                .collect();
+            if name.is_empty() {
+                return Err(ParseError::Syntax(
+                    "Syntax error, expected a name after letrec".to_string(),
+                ));
+            }
+            if KEYWORDS.contains(&name.as_str()) {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, {} is a keyword and cannot be the name of a letrec",
+                    name
+                )));
+            }
+            i = skip_spaces(&chars, i + name.chars().count());
+            if chars.get(i) != Some(&'=') {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, expected = after letrec {}",
+                    name
+                )));
+            }
+            //the definition ends at the in of this letrec, counting the letrecs nested in it
+            let def_start = i + 1;
+            let mut depth = 0;
+            let mut open = 1;
+            let mut def_end = None;
+            for j in def_start..chars.len() {
+                match chars[j] {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => break,
+                    ')' => depth -= 1,
+                    _ if depth == 0 && keyword_at(&chars, j, "letrec") => open += 1,
+                    _ if depth == 0 && keyword_at(&chars, j, "in") => {
+                        open -= 1;
+                        if open == 0 {
+                            def_end = Some(j);
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            let def_end = match def_end {
+                Some(j) => j,
+                None => {
+                    return Err(ParseError::Syntax(format!(
+                        "Syntax error, expected in after letrec {}",
+                        name
+                    )));
+                }
+            };
+            //the body goes on until the bracket around the letrec closes
+            let body_start = def_end + 2;
+            let mut depth = 0;
+            let mut body_end = chars.len();
+            for (j, c) in chars.iter().enumerate().skip(body_start) {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 0 => {
+                        body_end = j;
+                        break;
+                    }
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+            }
+            //an in right after the dot of a function is that function using in as a variable
+            if chars[def_start..def_end].iter().rev().find(|c| **c != ' ') == Some(&'.') {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, in is a keyword and cannot be a variable in letrec {}",
+                    name
+                )));
+            }
+            let def: String = chars[def_start..def_end].iter().collect();
+            let body: String = chars[body_start..body_end].iter().collect();
+            if def.trim().is_empty() {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, empty definition in letrec {}",
+                    name
+                )));
+            }
+            if body.trim().is_empty() {
+                return Err(ParseError::Syntax(format!(
+                    "Syntax error, expected a lambda after the in of letrec {}",
+                    name
+                )));
+            }
+            let out = format!(
+                "(({} ({} (%{}.({})))) (%{}.({})))",
+                THEN,
+                FIX,
+                name,
+                def.trim(),
+                name,
+                body.trim()
+            );
+            chars.splice(start..body_end, out.chars());
+        }
+        Ok(chars.into_iter().collect())
+    }
+}
+
+//check if the word is at the index as a token of its own
+fn keyword_at(chars: &[char], i: usize, word: &str) -> bool {
+    let len = word.chars().count();
+    let before = i == 0 || matches!(chars[i - 1], ' ' | '(' | '.');
+    let after = chars
+        .get(i + len)
+        .is_none_or(|c| matches!(c, ' ' | '(' | ')'));
+    before && after && chars[i..].iter().take(len).copied().eq(word.chars())
+}
+
+//check if a data declaration starts at the index, at the start of the lambda or after the ; of the one before it
+fn declaration_at(chars: &[char], i: usize) -> bool {
+    keyword_at(chars, i, "data")
+        && chars[..i]
+            .iter()
+            .rev()
+            .find(|c| **c != ' ')
+            .is_none_or(|c| *c == ';')
+}
+
+//check if the variable is free in the lambda
+fn is_free(l: &Lambda, var: &Lambda) -> bool {
+    match var {
+        Lambda::Variable(a) => l.free_vars().iter().any(|v| v == a.as_str()),
+        _ => false,
+    }
+}
+
+//index of the first character from i that is not a space
+fn skip_spaces(chars: &[char], mut i: usize) -> usize {
+    while chars.get(i) == Some(&' ') {
+        i += 1;
+    }
+    i
+}
+
+//split the text at every separator outside of brackets, braces, tuples and lists
+fn split_top(s: &str, sep: char) -> Vec<String> {
+    let mut out = vec![String::new()];
+    let mut depth = 0;
+    let mut last = ' ';
+    for c in s.chars() {
+        match c {
+            '(' | '{' | '<' | '[' => depth += 1,
+            //the > of a -> does not close a tuple
+            '>' if last == '-' => {}
+            ')' | '}' | '>' | ']' => depth -= 1,
+            _ => {}
+        }
+        last = c;
+        if c == sep && depth == 0 {
+            out.push(String::new());
+        } else {
+            out.last_mut().unwrap().push(c);
+        }
+    }
+    out
+}
+
+//names for n variables that are not used in the text or free in its inputs
+fn fresh_names(text: &str, inputs: &[String], n: usize) -> Vec<String> {
+    let used: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).collect();
+    (0..)
+        .map(|i| Lambda::get_name(i).to_string())
+        .filter(|v| !used.contains(&v.as_str()) && !inputs.contains(v))
+        .take(n)
+        .collect()
+}
@@ -0,0 +1,127 @@
+//! Interned variable names
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{LazyLock, Mutex};
+
+///Variable name, stored once and referred to by a small id
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let a = Symbol::new("var");
+///let b = Symbol::from("var");
+///assert_eq!(a, b);
+///println!("{}", a);
+/// //outputs var
+///```
+///
+///Symbols are Copy, and comparing or hashing them only compares their ids.
+///The names are kept for as long as the program runs, so every symbol with the same name has the same id.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Symbol(u32);
+
+//every name interned so far, with the symbols made by the naming scheme in order
+struct Interner {
+    names: Vec<&'static str>,
+    symbols: HashMap<&'static str, Symbol>,
+    numbered: Vec<Symbol>,
+}
+
+static INTERNER: LazyLock<Mutex<Interner>> = LazyLock::new(|| {
+    Mutex::new(Interner {
+        names: Vec::new(),
+        symbols: HashMap::new(),
+        numbered: Vec::new(),
+    })
+});
+
+impl Interner {
+    //find the symbol for a name, adding the name if it is new
+    fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(s) = self.symbols.get(name) {
+            return *s;
+        }
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        let s = Symbol(self.names.len() as u32);
+        self.names.push(name);
+        self.symbols.insert(name, s);
+        s
+    }
+}
+
+impl Symbol {
+    ///Get the symbol for a name
+    pub fn new(name: &str) -> Symbol {
+        INTERNER.lock().unwrap().intern(name)
+    }
+    ///Get the name of the symbol
+    pub fn as_str(self) -> &'static str {
+        INTERNER.lock().unwrap().names[self.0 as usize]
+    }
+    //get the nth name of the naming scheme, only building the names that were never made before
+    pub(crate) fn numbered(n: usize, alph: &str) -> Symbol {
+        let mut interner = INTERNER.lock().unwrap();
+        if let Some(s) = interner.numbered.get(n) {
+            return *s;
+        }
+        let chars: Vec<char> = alph.chars().collect();
+        for k in interner.numbered.len()..=n {
+            //write the digits of the name from the last one, then reverse them
+            let mut name: Vec<char> = Vec::new();
+            let mut m = k + 1;
+            while m != 0 {
+                name.push(chars[(m - 1) % chars.len()]);
+                m = (m - 1) / chars.len();
+            }
+            let name: String = name.iter().rev().collect();
+            let s = interner.intern(&name);
+            interner.numbered.push(s);
+        }
+        interner.numbered[n]
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(name: &str) -> Symbol {
+        Symbol::new(name)
+    }
+}
+
+impl From<&String> for Symbol {
+    fn from(name: &String) -> Symbol {
+        Symbol::new(name)
+    }
+}
+
+impl From<String> for Symbol {
+    fn from(name: String) -> Symbol {
+        Symbol::new(&name)
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+//implement display for symbols, writing their name
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+//implement debug for symbols like a string, so lambdas debug print their variable names
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
@@ -0,0 +1,553 @@
+//! System F explicit polymorphism layer
+//!
+//! Terms are written with explicit type abstractions and applications, typechecked, and then erased to an
+//! untyped [`Lambda`] for execution.
+//! # Example:
+//! ```rust
+//! use easy_lambda_calculus::*;
+//!
+//! let id = system_f!("ΛA.%x:A.x");
+//! println!("{}", id.typecheck().unwrap());
+//! //outputs ∀A. A -> A
+//! println!("{}", system_f!("{} [∀B. B -> B] {}", id.clone(), id).erase().evaluate());
+//! //outputs (%x.x)
+//! ```
+
+use crate::Lambda;
+use std::fmt;
+
+///Makes a new System F term from a string
+///
+///```rust
+///use easy_lambda_calculus::*;
+///
+///let t = system_f!("ΛA|B.%x:A|y:B.x");
+///println!("{}", t.typecheck().unwrap());
+/// //outputs ∀A. ∀B. A -> B -> A
+///```
+///
+///#### Syntax:
+///
+///%x:T.e to define a function with input variable x of type T, %x:A|y:B.e for multiple inputs.
+///
+///ΛA.e (or ^A.e) to define a type function with type variable A, ΛA|B.e for multiple type variables.
+///
+///f x to apply x into function f, application is left associative so f x y is (f x) y.
+///
+///e [T] to apply the type T into the type function e.
+///
+///Types are written as A -> B for functions, with -> being right associative, and ∀A. T (or forall A. T) for polymorphic types.
+///
+///Term variables are lowercase, type variables start with an uppercase letter.
+///
+///{} is used to input a System F term into the term, uses the same syntax as the `format!()` macro.
+#[macro_export]
+macro_rules! system_f {
+    ($x:expr) => (
+        $crate::system_f::Term::new($x, vec![])
+    );
+    ($x:expr, $($y:expr), +) => (
+        $crate::system_f::Term::new($x, vec![$($y), +])
+    );
+}
+
+///System F type
+#[derive(Debug, PartialEq, Clone)]
+pub enum Type {
+    ///Type variable
+    Variable(String),
+    ///Function type
+    Arrow((Box<Type>, Box<Type>)),
+    ///Universally quantified type
+    Forall((String, Box<Type>)),
+}
+
+///System F term
+#[derive(Debug, PartialEq, Clone)]
+pub enum Term {
+    ///Variable
+    Variable(String),
+    ///Function with an annotated input variable
+    Func((String, Type, Box<Term>)),
+    ///Marks a term being applied into a function
+    Reducible((Box<Term>, Box<Term>)),
+    ///Type function
+    TypeFunc((String, Box<Term>)),
+    ///Marks a type being applied into a type function
+    TypeReducible((Box<Term>, Type)),
+}
+
+///Error found while typechecking a System F term
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypeError {
+    ///A term variable is used outside of any function binding it
+    UnboundVariable(String),
+    ///A type variable is used outside of any type function or ∀ binding it
+    UnboundTypeVariable(String),
+    ///A term that is not a function is applied to an input
+    NotAFunction(Type),
+    ///A term that is not a type function is applied to a type
+    NotPolymorphic(Type),
+    ///A function input has a different type from the one it was annotated with
+    Mismatch {
+        ///The annotated input type
+        expected: Type,
+        ///The type of the applied input
+        found: Type,
+    },
+}
+
+impl Type {
+    //substitute every free instance of the type variable a with sub, renaming binders to avoid capture
+    fn substitute(&self, a: &str, sub: &Type) -> Type {
+        match self {
+            Self::Variable(v) => {
+                if v == a {
+                    return sub.clone();
+                }
+                self.clone()
+            }
+            Self::Arrow((l, r)) => Self::Arrow((
+                Box::new(l.substitute(a, sub)),
+                Box::new(r.substitute(a, sub)),
+            )),
+            Self::Forall((v, t)) => {
+                if v == a {
+                    return self.clone();
+                }
+                if sub.free_vars().contains(v) {
+                    let mut fresh = v.clone();
+                    while sub.free_vars().contains(&fresh) || t.free_vars().contains(&fresh) {
+                        fresh.push('\'');
+                    }
+                    let t = t.substitute(v, &Self::Variable(fresh.clone()));
+                    return Self::Forall((fresh, Box::new(t.substitute(a, sub))));
+                }
+                Self::Forall((v.clone(), Box::new(t.substitute(a, sub))))
+            }
+        }
+    }
+    //find every type variable that is not bound by a ∀
+    fn free_vars(&self) -> Vec<String> {
+        match self {
+            Self::Variable(v) => vec![v.clone()],
+            Self::Arrow((l, r)) => {
+                let mut out = l.free_vars();
+                out.extend(r.free_vars());
+                out
+            }
+            Self::Forall((v, t)) => t.free_vars().into_iter().filter(|x| x != v).collect(),
+        }
+    }
+    ///Check if two types are equal up to renaming of their ∀ bound type variables
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::system_f::*;
+    ///
+    ///let a = Type::new("∀A. A -> A");
+    ///let b = Type::new("∀B. B -> B");
+    ///assert!(a.alpha_eq(&b));
+    ///```
+    pub fn alpha_eq(&self, other: &Type) -> bool {
+        Self::recursive_alpha_eq(self, other, &mut vec![], &mut vec![])
+    }
+    //compare two types, with stacks of the bound variables in scope on each side
+    fn recursive_alpha_eq(a: &Type, b: &Type, sa: &mut Vec<String>, sb: &mut Vec<String>) -> bool {
+        match (a, b) {
+            (Self::Variable(x), Self::Variable(y)) => {
+                match (
+                    sa.iter().rposition(|v| v == x),
+                    sb.iter().rposition(|v| v == y),
+                ) {
+                    (Some(i), Some(j)) => i == j,
+                    (None, None) => x == y,
+                    _ => false,
+                }
+            }
+            (Self::Arrow((a1, a2)), Self::Arrow((b1, b2))) => {
+                Self::recursive_alpha_eq(a1, b1, sa, sb) && Self::recursive_alpha_eq(a2, b2, sa, sb)
+            }
+            (Self::Forall((x, a1)), Self::Forall((y, b1))) => {
+                sa.push(x.clone());
+                sb.push(y.clone());
+                let out = Self::recursive_alpha_eq(a1, b1, sa, sb);
+                sa.pop();
+                sb.pop();
+                out
+            }
+            _ => false,
+        }
+    }
+    ///Makes a new type from a string, see `system_f!()` for the syntax
+    pub fn new(s: &str) -> Type {
+        let mut p = Parser::new(s, vec![]);
+        let t = p.parse_type();
+        p.end();
+        t
+    }
+    //function to calculate a string to represent the type
+    fn display(&self) -> String {
+        match self {
+            Self::Variable(v) => v.clone(),
+            Self::Arrow((l, r)) => {
+                let s1 = match **l {
+                    Self::Variable(_) => l.display(),
+                    _ => format!("({})", l.display()),
+                };
+                format!("{} -> {}", &s1, r.display())
+            }
+            Self::Forall((v, t)) => format!("∀{}. {}", v, t.display()),
+        }
+    }
+}
+
+impl Term {
+    #[doc(hidden)]
+    pub fn new(s: &str, f: Vec<Term>) -> Term {
+        let mut p = Parser::new(s, f);
+        let t = p.parse_term();
+        p.end();
+        t
+    }
+    ///Typecheck a closed term, returning its type
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", system_f!("ΛA.%f:A -> A|x:A.f (f x)").typecheck().unwrap());
+    /// //outputs ∀A. (A -> A) -> A -> A
+    ///```
+    pub fn typecheck(&self) -> Result<Type, TypeError> {
+        self.recursive_typecheck(&mut vec![], &mut vec![])
+    }
+    //typecheck with stacks of the term variables and type variables in scope
+    fn recursive_typecheck(
+        &self,
+        vars: &mut Vec<(String, Type)>,
+        tys: &mut Vec<String>,
+    ) -> Result<Type, TypeError> {
+        match self {
+            Self::Variable(v) => match vars.iter().rev().find(|(x, _)| x == v) {
+                Some((_, t)) => Ok(t.clone()),
+                None => Err(TypeError::UnboundVariable(v.clone())),
+            },
+            Self::Func((v, t, b)) => {
+                Self::check_type(t, tys)?;
+                vars.push((v.clone(), t.clone()));
+                let out = b.recursive_typecheck(vars, tys);
+                vars.pop();
+                Ok(Type::Arrow((Box::new(t.clone()), Box::new(out?))))
+            }
+            Self::Reducible((a, b)) => {
+                let f = a.recursive_typecheck(vars, tys)?;
+                let x = b.recursive_typecheck(vars, tys)?;
+                if let Type::Arrow((c, d)) = f {
+                    if c.alpha_eq(&x) {
+                        return Ok(*d);
+                    }
+                    return Err(TypeError::Mismatch {
+                        expected: *c,
+                        found: x,
+                    });
+                }
+                Err(TypeError::NotAFunction(f))
+            }
+            Self::TypeFunc((v, b)) => {
+                //rename a type variable shadowing one in scope, so the types of outer variables are not captured
+                if tys.contains(v) {
+                    let mut fresh = v.clone();
+                    while tys.contains(&fresh) {
+                        fresh.push('\'');
+                    }
+                    let b = b.substitute_type(v, &Type::Variable(fresh.clone()));
+                    return Self::TypeFunc((fresh, Box::new(b))).recursive_typecheck(vars, tys);
+                }
+                tys.push(v.clone());
+                let out = b.recursive_typecheck(vars, tys);
+                tys.pop();
+                Ok(Type::Forall((v.clone(), Box::new(out?))))
+            }
+            Self::TypeReducible((a, t)) => {
+                Self::check_type(t, tys)?;
+                let f = a.recursive_typecheck(vars, tys)?;
+                if let Type::Forall((v, b)) = f {
+                    return Ok(b.substitute(&v, t));
+                }
+                Err(TypeError::NotPolymorphic(f))
+            }
+        }
+    }
+    //substitute every free instance of the type variable a in the type annotations of the term
+    fn substitute_type(&self, a: &str, sub: &Type) -> Term {
+        match self {
+            Self::Variable(_) => self.clone(),
+            Self::Func((v, t, b)) => Self::Func((
+                v.clone(),
+                t.substitute(a, sub),
+                Box::new(b.substitute_type(a, sub)),
+            )),
+            Self::Reducible((l, r)) => Self::Reducible((
+                Box::new(l.substitute_type(a, sub)),
+                Box::new(r.substitute_type(a, sub)),
+            )),
+            Self::TypeFunc((v, b)) => {
+                if v == a {
+                    return self.clone();
+                }
+                if sub.free_vars().contains(v) {
+                    let mut fresh = v.clone();
+                    while sub.free_vars().contains(&fresh) {
+                        fresh.push('\'');
+                    }
+                    let b = b.substitute_type(v, &Type::Variable(fresh.clone()));
+                    return Self::TypeFunc((fresh, Box::new(b.substitute_type(a, sub))));
+                }
+                Self::TypeFunc((v.clone(), Box::new(b.substitute_type(a, sub))))
+            }
+            Self::TypeReducible((l, t)) => {
+                Self::TypeReducible((Box::new(l.substitute_type(a, sub)), t.substitute(a, sub)))
+            }
+        }
+    }
+    //check every type variable in a type annotation is in scope
+    fn check_type(t: &Type, tys: &[String]) -> Result<(), TypeError> {
+        for v in t.free_vars() {
+            if !tys.contains(&v) {
+                return Err(TypeError::UnboundTypeVariable(v));
+            }
+        }
+        Ok(())
+    }
+    ///Erase every type from the term, giving the untyped lambda it computes
+    ///
+    ///```rust
+    ///use easy_lambda_calculus::*;
+    ///
+    ///println!("{}", system_f!("ΛA.%x:A|y:A.x").erase());
+    /// //outputs (%x|y.x)
+    ///```
+    ///
+    ///Type functions are replaced by their body and type applications by the term being applied, the result can be run with Lambda.evaluate().
+    pub fn erase(&self) -> Lambda {
+        match self {
+            Self::Variable(v) => Lambda::var(v),
+            Self::Func((v, _, b)) => Lambda::func(v, b.erase()),
+            Self::Reducible((a, b)) => a.erase().attach(b.erase()),
+            Self::TypeFunc((_, b)) => b.erase(),
+            Self::TypeReducible((a, _)) => a.erase(),
+        }
+    }
+    //function to calculate a string to represent the term
+    fn display(&self) -> String {
+        match self {
+            Self::Variable(v) => v.clone(),
+            Self::Func((v, t, b)) => {
+                let mut s1 = format!("{}:{}", v, t);
+                let mut b = b;
+                while let Self::Func((v, t, c)) = &**b {
+                    s1.push_str(&format!("|{}:{}", v, t));
+                    b = c;
+                }
+                format!("(%{}.{})", &s1, b.display())
+            }
+            Self::Reducible((a, b)) => format!("({} {})", a.display(), b.display()),
+            Self::TypeFunc((v, b)) => {
+                let mut s1 = v.clone();
+                let mut b = b;
+                while let Self::TypeFunc((v, c)) = &**b {
+                    s1.push('|');
+                    s1.push_str(v);
+                    b = c;
+                }
+                format!("(Λ{}.{})", &s1, b.display())
+            }
+            Self::TypeReducible((a, t)) => format!("({} [{}])", a.display(), t),
+        }
+    }
+}
+
+//recursive descent parser for terms and types
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    inputs: std::vec::IntoIter<Term>,
+}
+
+impl Parser {
+    fn new(s: &str, f: Vec<Term>) -> Parser {
+        Parser {
+            chars: s.chars().collect(),
+            pos: 0,
+            inputs: f.into_iter(),
+        }
+    }
+    //skip whitespace and look at the next character
+    fn peek(&mut self) -> Option<char> {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1;
+        }
+        self.chars.get(self.pos).copied()
+    }
+    //check if the upcoming characters are s, consuming them if they are
+    fn eat(&mut self, s: &str) -> bool {
+        self.peek();
+        let c: Vec<char> = s.chars().collect();
+        if self.chars[self.pos..].starts_with(&c) {
+            self.pos += c.len();
+            return true;
+        }
+        false
+    }
+    fn expect(&mut self, s: &str) {
+        if !self.eat(s) {
+            panic!("Syntax error, expected {} at {}", s, self.pos);
+        }
+    }
+    fn end(&mut self) {
+        if self.peek().is_some() {
+            panic!("Syntax error at {}", self.pos);
+        }
+    }
+    //read an identifier
+    fn ident(&mut self) -> String {
+        self.peek();
+        let start = self.pos;
+        while self.pos < self.chars.len() && self.chars[self.pos].is_ascii_alphabetic() {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            panic!("Syntax error, expected a variable at {}", self.pos);
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+    fn term_var(&mut self) -> String {
+        let v = self.ident();
+        if !v.chars().all(|c| Lambda::ALPH.contains(c)) {
+            panic!("Syntax error, term variable {} must be lowercase", v);
+        }
+        v
+    }
+    fn type_var(&mut self) -> String {
+        let v = self.ident();
+        if !v.starts_with(|c: char| c.is_ascii_uppercase()) {
+            panic!(
+                "Syntax error, type variable {} must start with an uppercase letter",
+                v
+            );
+        }
+        v
+    }
+    fn parse_term(&mut self) -> Term {
+        let mut t = self.parse_atom();
+        loop {
+            match self.peek() {
+                Some('[') => {
+                    self.pos += 1;
+                    let ty = self.parse_type();
+                    self.expect("]");
+                    t = Term::TypeReducible((Box::new(t), ty));
+                }
+                Some(c) if c != ')' && c != ']' => {
+                    let b = self.parse_atom();
+                    t = Term::Reducible((Box::new(t), Box::new(b)));
+                }
+                _ => return t,
+            }
+        }
+    }
+    fn parse_atom(&mut self) -> Term {
+        if self.eat("(") {
+            let t = self.parse_term();
+            self.expect(")");
+            t
+        } else if self.eat("{}") {
+            match self.inputs.next() {
+                Some(t) => t,
+                None => panic!("Missing input for {{}}"),
+            }
+        } else if self.eat("%") || self.eat("λ") {
+            let mut vars = Vec::new();
+            loop {
+                let v = self.term_var();
+                self.expect(":");
+                vars.push((v, self.parse_type()));
+                if !self.eat("|") {
+                    break;
+                }
+            }
+            self.expect(".");
+            let mut t = self.parse_term();
+            for (v, ty) in vars.into_iter().rev() {
+                t = Term::Func((v, ty, Box::new(t)));
+            }
+            t
+        } else if self.eat("Λ") || self.eat("^") {
+            let mut vars = vec![self.type_var()];
+            while self.eat("|") {
+                vars.push(self.type_var());
+            }
+            self.expect(".");
+            let mut t = self.parse_term();
+            for v in vars.into_iter().rev() {
+                t = Term::TypeFunc((v, Box::new(t)));
+            }
+            t
+        } else {
+            Term::Variable(self.term_var())
+        }
+    }
+    fn parse_type(&mut self) -> Type {
+        if self.eat("∀") || self.eat("forall ") {
+            let mut vars = vec![self.type_var()];
+            while self.eat("|") || self.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                vars.push(self.type_var());
+            }
+            self.expect(".");
+            let mut t = self.parse_type();
+            for v in vars.into_iter().rev() {
+                t = Type::Forall((v, Box::new(t)));
+            }
+            return t;
+        }
+        let l = if self.eat("(") {
+            let t = self.parse_type();
+            self.expect(")");
+            t
+        } else {
+            Type::Variable(self.type_var())
+        };
+        if self.eat("->") || self.eat("→") {
+            return Type::Arrow((Box::new(l), Box::new(self.parse_type())));
+        }
+        l
+    }
+}
+
+//implement display for the System F data types
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display())
+    }
+}
+
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.display())
+    }
+}
+
+impl fmt::Display for TypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnboundVariable(v) => write!(f, "Unbound variable {}", v),
+            Self::UnboundTypeVariable(v) => write!(f, "Unbound type variable {}", v),
+            Self::NotAFunction(t) => write!(f, "Cannot apply a term of type {}", t),
+            Self::NotPolymorphic(t) => write!(f, "Cannot apply a type into a term of type {}", t),
+            Self::Mismatch { expected, found } => {
+                write!(f, "Expected input of type {}, found {}", expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TypeError {}
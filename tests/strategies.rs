@@ -0,0 +1,64 @@
+//check every evaluation strategy gives the same normal forms, run with cargo test
+
+use easy_lambda_calculus::*;
+
+//strategies stopping at the outer function, compared with substitution
+const WEAK: [Strategy; 4] = [
+    Strategy::Krivine,
+    Strategy::CallByNeed,
+    Strategy::CallByValue,
+    Strategy::Bytecode,
+];
+
+//strategies reducing under functions, compared with nbe
+const FULL: [Strategy; 1] = [Strategy::Optimal];
+
+//closed terms covering church arithmetic, shadowing and sharing
+const TERMS: [&str; 16] = [
+    //church arithmetic
+    "((%m|n|f|x.((m f) ((n f) x))) #2) #3",
+    "((%m|n|f.(m (n f))) #2) #3",
+    "(#3 #2)",
+    "((%n|f|x.(((n (%g|h.(h (g f)))) (%u.x)) (%u.u))) #3)",
+    "(((#4 (%b|x|y.((b y) x))) (%x|y.x)) (%a.a)) (%a|b.b)",
+    //shadowing
+    "(%x.&(%x.&(%x.x))) &(%x.x)",
+    "((%x|x.x) (%y.y)) (%z.z)",
+    "((%x|y.(y x)) (%y.y)) (%z.z)",
+    "(%x.(%y.(%x.(y x)))) (%z.z)",
+    "((%x|y.(x y)) (%y|x.(y x))) (%x.x)",
+    //sharing
+    "(%x.((x x) x)) ((%y.y) (%z.z))",
+    "(%f.(f (f (%z.z)))) (%x.((%y.y) x))",
+    "(((#3 #3) (%x.x)) (%y.y))",
+    "(%x.(x x)) (%f|x.(f (f x)))",
+    "((%x.(x (x (%y.y)))) ((%f|x.(f x)) (%a.a)))",
+    "((#2 (%x.(x x))) (%y.y))",
+];
+
+#[test]
+fn weak_strategies_agree() {
+    for t in TERMS {
+        let l = Lambda::new(t, vec![]);
+        let expected = DeBruijn::from(&l.clone().evaluate());
+        for strategy in WEAK {
+            let out = DeBruijn::from(&l.clone().evaluate_with(strategy));
+            assert_eq!(out, expected, "{:?} on {}", strategy, t);
+        }
+    }
+}
+
+#[test]
+fn full_strategies_agree() {
+    for t in TERMS {
+        let l = Lambda::new(t, vec![]);
+        let expected = DeBruijn::from(&l.clone().evaluate_with(Strategy::Nbe));
+        for strategy in FULL {
+            let out = DeBruijn::from(&l.clone().evaluate_with(strategy));
+            assert_eq!(out, expected, "{:?} on {}", strategy, t);
+        }
+        //normalizing the weak result reaches the same normal form
+        let weak = l.clone().evaluate().evaluate_with(Strategy::Nbe);
+        assert_eq!(DeBruijn::from(&weak), expected, "substitution on {}", t);
+    }
+}